@@ -0,0 +1,866 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::sync::OnceLock;
+use serde::Deserialize;
+use quick_xml::Writer;
+
+use super::xml;
+
+
+/// The `bcf:datamodel`, with lookups by entry type and field built once
+/// when it is loaded.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "Definitions")]
+pub struct Datamodel {
+    definitions: Definitions,
+    index: Index,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct Definitions {
+    constants: ConstantDefinitions,
+    entrytypes: EntrytypeDefinitions,
+    fields: FieldDefinitions,
+    entryfields: Vec<EntrytypesAndFields>,
+    multiscriptfields: Fields,
+    #[serde(default)] constraints: Vec<ConstraintSet>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Index {
+    entrytypes: HashMap<String, usize>,
+    fields: HashMap<String, usize>,
+    /// Allowed fields by entry type, those for all entry types first.
+    entryfields: HashMap<String, (Vec<String>, HashSet<String>)>,
+    /// Fields allowed for entry types unknown to the datamodel.
+    common_fields: (Vec<String>, HashSet<String>),
+    mandatory: HashMap<String, Vec<Mandatory>>,
+    multiscript: HashSet<String>,
+    /// Constants of the datamodel over biblatex's defaults.
+    constants: HashMap<String, ConstantValue>,
+}
+
+/// A `mandatory` constraint on an entry type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mandatory {
+    Field(String),
+    /// At least one of the fields (`fieldor`).
+    AnyOf(Vec<String>),
+    /// Exactly one of the fields (`fieldxor`).
+    OneOf(Vec<String>),
+}
+
+impl Datamodel {
+
+    /// Names of the entry types, in no particular order.
+    pub fn valid_entrytypes(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.index.entrytypes.keys().map(String::as_str)
+    }
+
+    /// Names of the fields, in no particular order.
+    pub fn valid_fields(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.index.fields.keys().map(String::as_str)
+    }
+
+    /// Entry types in the order of the datamodel.
+    pub fn entrytypes(&self) -> impl Iterator<Item = &EntrytypeDefinition> {
+        self.definitions.entrytypes.entrytype.iter()
+    }
+
+    /// Fields in the order of the datamodel.
+    pub fn fields(&self) -> impl Iterator<Item = &FieldDefinition> {
+        self.definitions.fields.field.iter()
+    }
+
+    pub fn entrytype(&self, name: &str) -> Option<&EntrytypeDefinition> {
+        self.index.entrytypes.get(name).map(|i| &self.definitions.entrytypes.entrytype[*i])
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldDefinition> {
+        self.index.fields.get(name).map(|i| &self.definitions.fields.field[*i])
+    }
+
+    pub fn is_entrytype(&self, name: &str) -> bool {
+        self.index.entrytypes.contains_key(name)
+    }
+
+    /// Fields `entryfields` allow for `entrytype`, either for all entry
+    /// types or for this one.
+    pub fn fields_for_entrytype(&self, entrytype: &str) -> &[String] {
+        &self.index.entryfields.get(entrytype).unwrap_or(&self.index.common_fields).0
+    }
+
+    /// Whether `entryfields` allow `field` for `entrytype`, either for all
+    /// entry types or for this one.
+    pub fn is_field_for_entrytype(&self, entrytype: &str, field: &str) -> bool {
+        self.index.entryfields.get(entrytype).unwrap_or(&self.index.common_fields).1.contains(field)
+    }
+
+    /// The `mandatory` constraints for all entry types and for `entrytype`.
+    pub fn mandatory_fields(&self, entrytype: &str) -> &[Mandatory] {
+        self.index.mandatory.get(entrytype).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `field` may have alternate scripts (`multiscriptfields`).
+    pub fn is_multiscript(&self, field: &str) -> bool {
+        self.index.multiscript.contains(field)
+    }
+
+    /// A `bcf:constant`, or biblatex's default for the constants below.
+    pub fn constant(&self, name: &str) -> Option<&ConstantValue> {
+        self.index.constants.get(name)
+    }
+
+    /// Parts of a name (`nameparts`), e.g. `family` and `given`.
+    pub fn nameparts(&self) -> &[String] {
+        self.list_constant("nameparts")
+    }
+
+    /// Values of the `gender` field (`gender`), e.g. `sf` for a single female.
+    pub fn genders(&self) -> &[String] {
+        self.list_constant("gender")
+    }
+
+    /// Forms of alternate scripts (`multiscriptforms`), e.g. `transliteration`.
+    pub fn multiscript_forms(&self) -> &[String] {
+        self.list_constant("multiscriptforms")
+    }
+
+    /// Datatypes of per-entry options (`optiondatatypes`).
+    pub fn option_datatypes(&self) -> &[String] {
+        self.list_constant("optiondatatypes")
+    }
+
+    fn list_constant(&self, name: &str) -> &[String] {
+        match self.constant(name) {
+            Some(ConstantValue::List(items)) => items,
+            _ => &[],
+        }
+    }
+
+    /// Constraints for all entry types and for `entrytype`.
+    pub fn constraints<'a>(&'a self, entrytype: &'a str) -> impl Iterator<Item = &'a Constraint> {
+        self.definitions.constraints.iter()
+            .filter(move |set| set.entrytype.is_empty() || set.entrytype.iter().any(|other| other == entrytype))
+            .flat_map(|set| &set.constraint)
+    }
+}
+
+impl From<Definitions> for Datamodel {
+    fn from(definitions: Definitions) -> Self {
+        let mut index = Index {
+            entrytypes: definitions.entrytypes.entrytype.iter().enumerate().map(|(i, def)| (def.name.clone(), i)).collect(),
+            fields: definitions.fields.field.iter().enumerate().map(|(i, def)| (def.name.clone(), i)).collect(),
+            multiscript: definitions.multiscriptfields.field.iter().cloned().collect(),
+            constants: DEFAULT_CONSTANTS.iter()
+                .map(|(name, items)| (name.to_string(), ConstantValue::List(items.iter().map(|item| item.to_string()).collect())))
+                .chain(definitions.constants.constant.iter().map(|constant| (constant.name.clone(), constant.value.clone())))
+                .collect(),
+            ..Index::default()
+        };
+
+        let allow = |allowed: &mut (Vec<String>, HashSet<String>), fields: &[String]| {
+            for field in fields {
+                if allowed.1.insert(field.clone()) {
+                    allowed.0.push(field.clone());
+                }
+            }
+        };
+        for def in definitions.entryfields.iter().filter(|def| def.entrytype.is_empty()) {
+            allow(&mut index.common_fields, &def.field);
+        }
+        for entrytype in &definitions.entrytypes.entrytype {
+            let mut allowed = index.common_fields.clone();
+            for def in definitions.entryfields.iter().filter(|def| def.entrytype.contains(&entrytype.name)) {
+                allow(&mut allowed, &def.field);
+            }
+            index.entryfields.insert(entrytype.name.clone(), allowed);
+        }
+
+        let datamodel = Datamodel { definitions, index: Index::default() };
+        for entrytype in &datamodel.definitions.entrytypes.entrytype {
+            let mandatory: Vec<Mandatory> = datamodel.constraints(&entrytype.name)
+                .filter(|constraint| constraint.r#type == ConstraintType::Mandatory)
+                .flat_map(|constraint| &constraint.items)
+                .filter_map(|item| match item {
+                    ConstraintItem::Field(field) => Some(Mandatory::Field(field.clone())),
+                    ConstraintItem::FieldOr(group) => Some(Mandatory::AnyOf(group.field.clone())),
+                    ConstraintItem::FieldXor(group) => Some(Mandatory::OneOf(group.field.clone())),
+                    _ => None,
+                })
+                .collect();
+            index.mandatory.insert(entrytype.name.clone(), mandatory);
+        }
+        Datamodel { index, ..datamodel }
+    }
+}
+
+/// biblatex's default datamodel, as written by biblatex 3.16 without
+/// customisations. It is parsed once and cloned.
+impl Default for Datamodel {
+    fn default() -> Self {
+        static DEFAULT: OnceLock<Datamodel> = OnceLock::new();
+        DEFAULT.get_or_init(|| {
+            let controlfile: super::Controlfile = DEFAULT_DATAMODEL.parse()
+                .expect("the bundled datamodel is valid");
+            controlfile.datamodel
+        }).clone()
+    }
+}
+
+const DEFAULT_DATAMODEL: &str = include_str!("../../resources/default-datamodel.bcf");
+
+/// Constants biblatex always defines, for datamodels without them.
+const DEFAULT_CONSTANTS: [(&str, &[&str]); 4] = [
+    ("gender", &["sf", "sm", "sn", "pf", "pm", "pn", "pp"]),
+    ("nameparts", &["family", "given", "prefix", "suffix"]),
+    ("optiondatatypes", &["boolean", "integer", "string", "xml"]),
+    ("multiscriptforms", &["default", "transliteration", "transcription", "translation"]),
+];
+
+impl PartialEq for Datamodel {
+    fn eq(&self, other: &Self) -> bool {
+        self.definitions == other.definitions
+    }
+}
+
+
+// Builder
+// -------
+
+/// Builds a [`Datamodel`] without a control file, from scratch or by
+/// extending another one. Definitions of the same name are replaced.
+#[derive(Debug, Clone)]
+pub struct DatamodelBuilder {
+    definitions: Definitions,
+}
+
+impl DatamodelBuilder {
+
+    /// An empty datamodel.
+    pub fn new() -> Self {
+        DatamodelBuilder {
+            definitions: Definitions {
+                constants: ConstantDefinitions { constant: Vec::new() },
+                entrytypes: EntrytypeDefinitions { entrytype: Vec::new() },
+                fields: FieldDefinitions { field: Vec::new() },
+                entryfields: Vec::new(),
+                multiscriptfields: Fields::default(),
+                constraints: Vec::new(),
+            },
+        }
+    }
+
+    pub fn constant<N: Into<String>>(&mut self, name: N, value: ConstantValue) -> &mut Self {
+        let constant = Constant { name: name.into(), value };
+        let constants = &mut self.definitions.constants.constant;
+        match constants.iter_mut().find(|other| other.name == constant.name) {
+            Some(other) => *other = constant,
+            None => constants.push(constant),
+        }
+        self
+    }
+
+    pub fn entrytype<N: Into<String>>(&mut self, name: N, skip_output: bool) -> &mut Self {
+        let def = EntrytypeDefinition { name: name.into(), skip_output };
+        let entrytypes = &mut self.definitions.entrytypes.entrytype;
+        match entrytypes.iter_mut().find(|other| other.name == def.name) {
+            Some(other) => *other = def,
+            None => entrytypes.push(def),
+        }
+        self
+    }
+
+    pub fn field(&mut self, def: FieldDefinition) -> &mut Self {
+        let fields = &mut self.definitions.fields.field;
+        match fields.iter_mut().find(|other| other.name == def.name) {
+            Some(other) => *other = def,
+            None => fields.push(def),
+        }
+        self
+    }
+
+    /// Allows `fields` for `entrytypes`, or for all entry types if there are
+    /// none.
+    pub fn entryfields(&mut self, entrytypes: &[&str], fields: &[&str]) -> &mut Self {
+        self.definitions.entryfields.push(EntrytypesAndFields {
+            entrytype: entrytypes.iter().map(|entrytype| entrytype.to_string()).collect(),
+            field: fields.iter().map(|field| field.to_string()).collect(),
+        });
+        self
+    }
+
+    pub fn multiscript_field<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        let name = name.into();
+        if !self.definitions.multiscriptfields.field.contains(&name) {
+            self.definitions.multiscriptfields.field.push(name);
+        }
+        self
+    }
+
+    /// Adds `constraints` for `entrytypes`, or for all entry types if there
+    /// are none.
+    pub fn constraints(&mut self, entrytypes: &[&str], constraints: Vec<Constraint>) -> &mut Self {
+        self.definitions.constraints.push(ConstraintSet {
+            entrytype: entrytypes.iter().map(|entrytype| entrytype.to_string()).collect(),
+            constraint: constraints,
+        });
+        self
+    }
+
+    pub fn build(&self) -> Datamodel {
+        Datamodel::from(self.definitions.clone())
+    }
+}
+
+impl Default for DatamodelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Datamodel> for DatamodelBuilder {
+    fn from(datamodel: Datamodel) -> Self {
+        DatamodelBuilder { definitions: datamodel.definitions }
+    }
+}
+
+impl FieldDefinition {
+
+    pub fn new<N: Into<String>>(name: N, fieldtype: FieldType, datatype: DataType) -> Self {
+        FieldDefinition {
+            name: name.into(),
+            fieldtype,
+            datatype,
+            format: String::new(),
+            skip_output: false,
+            nullok: false,
+            label: false,
+        }
+    }
+
+    pub fn with_format<F: Into<String>>(mut self, format: F) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    pub fn with_skip_output(mut self, skip_output: bool) -> Self {
+        self.skip_output = skip_output;
+        self
+    }
+
+    pub fn with_nullok(mut self, nullok: bool) -> Self {
+        self.nullok = nullok;
+        self
+    }
+
+    pub fn with_label(mut self, label: bool) -> Self {
+        self.label = label;
+        self
+    }
+}
+
+impl Constraint {
+
+    fn new(r#type: ConstraintType, items: Vec<ConstraintItem>) -> Self {
+        Constraint {
+            r#type,
+            datatype: None,
+            pattern: None,
+            rangemin: None,
+            rangemax: None,
+            rangestart: None,
+            rangeend: None,
+            items,
+        }
+    }
+
+    /// Fields that must be present: [`ConstraintItem::Field`],
+    /// [`ConstraintItem::FieldOr`] and [`ConstraintItem::FieldXor`].
+    pub fn mandatory(items: Vec<ConstraintItem>) -> Self {
+        Constraint::new(ConstraintType::Mandatory, items)
+    }
+
+    /// Check of `fields` for `datatype`; set [`Constraint::pattern`] or the
+    /// range limits as needed.
+    pub fn data(datatype: &str, fields: &[&str]) -> Self {
+        let items = fields.iter().map(|field| ConstraintItem::Field(field.to_string())).collect();
+        Constraint { datatype: Some(datatype.to_owned()), ..Constraint::new(ConstraintType::Data, items) }
+    }
+
+    pub fn conditional(antecedent: Condition, consequent: Condition) -> Self {
+        let items = vec![ConstraintItem::Antecedent(antecedent), ConstraintItem::Consequent(consequent)];
+        Constraint::new(ConstraintType::Conditional, items)
+    }
+}
+
+
+// Merging
+// -------
+
+impl Datamodel {
+
+    /// The union of both datamodels, e.g. the default one and an extension
+    /// with further entry types. Definitions in both must be the same.
+    pub fn merge(&self, other: &Datamodel) -> Result<Datamodel, Conflict> {
+        // same definitions are replaced by themselves
+        let mut builder = DatamodelBuilder::from(self.clone());
+        for constant in &other.definitions.constants.constant {
+            let constants = &self.definitions.constants.constant;
+            if let Some(own) = constants.iter().find(|own| own.name == constant.name && own.value != constant.value) {
+                return Err(Conflict::Constant(constant.name.clone(), own.value.clone(), constant.value.clone()));
+            }
+            builder.constant(constant.name.clone(), constant.value.clone());
+        }
+        for def in other.entrytypes() {
+            if self.entrytype(&def.name).is_some_and(|own| own != def) {
+                return Err(Conflict::Entrytype(def.name.clone()));
+            }
+            builder.entrytype(def.name.clone(), def.skip_output);
+        }
+        for def in other.fields() {
+            if let Some(own) = self.field(&def.name).filter(|own| *own != def) {
+                return Err(Conflict::Field(own.clone(), def.clone()));
+            }
+            builder.field(def.clone());
+        }
+        for field in &other.definitions.multiscriptfields.field {
+            builder.multiscript_field(field.clone());
+        }
+
+        let definitions = &mut builder.definitions;
+        for def in &other.definitions.entryfields {
+            if !definitions.entryfields.contains(def) {
+                definitions.entryfields.push(def.clone());
+            }
+        }
+        for set in &other.definitions.constraints {
+            if !definitions.constraints.contains(set) {
+                definitions.constraints.push(set.clone());
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Definitions of the same name that differ between merged datamodels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    Constant(String, ConstantValue, ConstantValue),
+    /// Entry type output in one datamodel and skipped in the other.
+    Entrytype(String),
+    Field(FieldDefinition, FieldDefinition),
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Conflict::Constant(name, a, b) => write!(
+                f, "Conflicting definitions of constant '{}': '{}' and '{}'", name, a.text(), b.text(),
+            ),
+            Conflict::Entrytype(name) => write!(f, "Conflicting definitions of entry type '{}': skip_output differs", name),
+            Conflict::Field(a, b) => write!(
+                f, "Conflicting definitions of field '{}': {} and {}", a.name, a.description(), b.description(),
+            ),
+        }
+    }
+}
+
+impl FieldDefinition {
+
+    /// Attributes as in the control file, e.g. `fieldtype=list datatype=name`.
+    fn description(&self) -> String {
+        let mut text = format!("fieldtype={} datatype={}", self.fieldtype.name(), self.datatype.name());
+        if !self.format.is_empty() {
+            text.push_str(&format!(" format={}", self.format));
+        }
+        for (flag, name) in [(self.skip_output, "skip_output"), (self.nullok, "nullok"), (self.label, "label")] {
+            if flag {
+                text.push_str(&format!(" {}=true", name));
+            }
+        }
+        text
+    }
+}
+
+
+// Definitions
+// -----------
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct ConstantDefinitions {
+    #[serde(default)] constant: Vec<Constant>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(try_from = "RawConstant")]
+struct Constant {
+    name: String,
+    value: ConstantValue,
+}
+
+/// Value of a `bcf:constant`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantValue {
+    String(String),
+    /// Comma separated in the control file.
+    List(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct RawConstant {
+    name: String,
+    r#type: String,
+    #[serde(rename = "$value", default)]
+    value: String,
+}
+
+impl TryFrom<RawConstant> for Constant {
+    type Error = String;
+
+    fn try_from(raw: RawConstant) -> Result<Self, String> {
+        let value = match raw.r#type.as_str() {
+            "string" => ConstantValue::String(raw.value),
+            "list" => ConstantValue::List(raw.value.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_owned).collect()),
+            other => return Err(format!("unknown type '{}' of constant '{}'", other, raw.name)),
+        };
+        Ok(Constant { name: raw.name, value })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct EntrytypeDefinitions {
+    #[serde(default)] entrytype: Vec<EntrytypeDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct EntrytypeDefinition {
+    #[serde(rename = "$value")] name: String,
+    #[serde(default)] skip_output: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct FieldDefinitions {
+    #[serde(default)] field: Vec<FieldDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct FieldDefinition {
+    #[serde(rename = "$value")] pub(crate) name: String,
+    pub(crate) fieldtype: FieldType,
+    pub(crate) datatype: DataType,
+    #[serde(default)] pub(crate) format: String,
+    #[serde(default)] skip_output: bool,
+    #[serde(default)] nullok: bool,
+    #[serde(default)] label: bool,
+}
+
+impl EntrytypeDefinition {
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Entries of this type are not written to the `.bbl`.
+    pub fn skip_output(&self) -> bool {
+        self.skip_output
+    }
+}
+
+impl FieldDefinition {
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fieldtype(&self) -> FieldType {
+        self.fieldtype
+    }
+
+    pub fn datatype(&self) -> DataType {
+        self.datatype
+    }
+
+    /// `xsv` for comma separated fields, empty otherwise.
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// Fields that are not written to the `.bbl`.
+    pub fn skip_output(&self) -> bool {
+        self.skip_output
+    }
+
+    /// Whether the field may be empty.
+    pub fn nullok(&self) -> bool {
+        self.nullok
+    }
+
+    /// Short forms used for labels, like `shorthand` or `shortauthor`.
+    pub fn label(&self) -> bool {
+        self.label
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Field,
+    List,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataType {
+    Name,
+    Literal,
+    Integer,
+    Key,
+    Entrykey,
+    Date,
+    Datepart,
+    Verbatim,
+    Uri,
+    Keyword,
+    Option,
+    Range,
+    Code,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct EntrytypesAndFields {
+    #[serde(default)] entrytype: Vec<String>,
+    #[serde(default)] field: Vec<String>,
+}
+
+/// A list of `bcf:field` elements.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct Fields {
+    #[serde(default)] pub field: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct ConstraintSet {
+    #[serde(default)] entrytype: Vec<String>,
+    #[serde(default)] constraint: Vec<Constraint>,
+}
+
+/// A `bcf:constraint`. Which attributes and children are used depends on
+/// the [`ConstraintType`].
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Constraint {
+    pub r#type: ConstraintType,
+    /// `integer`, `datepart`, `date`, `isbn`, `issn`, `ismn` or `pattern`.
+    #[serde(default)] pub datatype: Option<String>,
+    #[serde(default)] pub pattern: Option<String>,
+    #[serde(default)] pub rangemin: Option<i64>,
+    #[serde(default)] pub rangemax: Option<i64>,
+    /// Kept as written, these are not evaluated.
+    #[serde(default)] pub rangestart: Option<String>,
+    #[serde(default)] pub rangeend: Option<String>,
+    #[serde(rename = "$value", default)] pub items: Vec<ConstraintItem>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConstraintType {
+    /// Fields that must be present.
+    Mandatory,
+    /// Checks of the values of fields.
+    Data,
+    /// Fields that must be present if others are.
+    Conditional,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub enum ConstraintItem {
+    #[serde(rename = "bcf:field")] Field(String),
+    /// At least one of these fields is mandatory.
+    #[serde(rename = "bcf:fieldor")] FieldOr(Fields),
+    /// Exactly one of these fields is mandatory.
+    #[serde(rename = "bcf:fieldxor")] FieldXor(Fields),
+    #[serde(rename = "bcf:antecedent")] Antecedent(Condition),
+    #[serde(rename = "bcf:consequent")] Consequent(Condition),
+}
+
+/// `quant` of `field` are present.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Condition {
+    pub quant: Quantifier,
+    #[serde(default)] pub field: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantifier {
+    All,
+    One,
+    None,
+}
+
+
+// XML output
+// ----------
+
+impl Datamodel {
+
+    /// Writes the `bcf:datamodel` element as biblatex does.
+    pub fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:datamodel", &[])?;
+
+        xml::start(writer, "bcf:constants", &[])?;
+        for constant in &self.definitions.constants.constant {
+            let attributes = [("type", xml::attr(constant.value.type_name())), ("name", xml::attr(&constant.name))];
+            xml::text(writer, "bcf:constant", &attributes, &constant.value.text())?;
+        }
+        xml::end(writer, "bcf:constants")?;
+
+        xml::start(writer, "bcf:entrytypes", &[])?;
+        for def in &self.definitions.entrytypes.entrytype {
+            xml::text(writer, "bcf:entrytype", &[("skip_output", xml::flag(def.skip_output))], &def.name)?;
+        }
+        xml::end(writer, "bcf:entrytypes")?;
+
+        xml::start(writer, "bcf:fields", &[])?;
+        for def in &self.definitions.fields.field {
+            let attributes = [
+                ("fieldtype", Some(def.fieldtype.name().to_owned())),
+                ("datatype", Some(def.datatype.name().to_owned())),
+                ("format", Some(def.format.clone()).filter(|format| !format.is_empty())),
+                ("skip_output", xml::flag(def.skip_output)),
+                ("nullok", xml::flag(def.nullok)),
+                ("label", xml::flag(def.label)),
+            ];
+            xml::text(writer, "bcf:field", &attributes, &def.name)?;
+        }
+        xml::end(writer, "bcf:fields")?;
+
+        for def in &self.definitions.entryfields {
+            xml::start(writer, "bcf:entryfields", &[])?;
+            xml::texts(writer, "bcf:entrytype", &def.entrytype)?;
+            xml::texts(writer, "bcf:field", &def.field)?;
+            xml::end(writer, "bcf:entryfields")?;
+        }
+
+        xml::start(writer, "bcf:multiscriptfields", &[])?;
+        xml::texts(writer, "bcf:field", &self.definitions.multiscriptfields.field)?;
+        xml::end(writer, "bcf:multiscriptfields")?;
+
+        for set in &self.definitions.constraints {
+            xml::start(writer, "bcf:constraints", &[])?;
+            xml::texts(writer, "bcf:entrytype", &set.entrytype)?;
+            for constraint in &set.constraint {
+                constraint.write(writer)?;
+            }
+            xml::end(writer, "bcf:constraints")?;
+        }
+
+        xml::end(writer, "bcf:datamodel")
+    }
+}
+
+impl Constraint {
+
+    fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        let attributes = [
+            ("type", Some(self.r#type.name().to_owned())),
+            ("datatype", self.datatype.clone()),
+            ("pattern", self.pattern.clone()),
+            ("rangemin", self.rangemin.map(|min| min.to_string())),
+            ("rangemax", self.rangemax.map(|max| max.to_string())),
+            ("rangestart", self.rangestart.clone()),
+            ("rangeend", self.rangeend.clone()),
+        ];
+        xml::start(writer, "bcf:constraint", &attributes)?;
+        for item in &self.items {
+            let (name, fields, quant) = match item {
+                ConstraintItem::Field(field) => {
+                    xml::text(writer, "bcf:field", &[], field)?;
+                    continue;
+                },
+                ConstraintItem::FieldOr(group) => ("bcf:fieldor", &group.field, None),
+                ConstraintItem::FieldXor(group) => ("bcf:fieldxor", &group.field, None),
+                ConstraintItem::Antecedent(condition) => ("bcf:antecedent", &condition.field, Some(condition.quant)),
+                ConstraintItem::Consequent(condition) => ("bcf:consequent", &condition.field, Some(condition.quant)),
+            };
+            xml::start(writer, name, &[("quant", quant.map(|quant| quant.name().to_owned()))])?;
+            xml::texts(writer, "bcf:field", fields)?;
+            xml::end(writer, name)?;
+        }
+        xml::end(writer, "bcf:constraint")
+    }
+}
+
+impl ConstantValue {
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            ConstantValue::String(_) => "string",
+            ConstantValue::List(_) => "list",
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            ConstantValue::String(text) => text.clone(),
+            ConstantValue::List(items) => items.join(","),
+        }
+    }
+}
+
+impl FieldType {
+
+    /// Name as used in the control file.
+    pub fn name(self) -> &'static str {
+        match self {
+            FieldType::Field => "field",
+            FieldType::List => "list",
+        }
+    }
+}
+
+impl DataType {
+
+    /// Name as used in the control file.
+    pub fn name(self) -> &'static str {
+        match self {
+            DataType::Name => "name",
+            DataType::Literal => "literal",
+            DataType::Integer => "integer",
+            DataType::Key => "key",
+            DataType::Entrykey => "entrykey",
+            DataType::Date => "date",
+            DataType::Datepart => "datepart",
+            DataType::Verbatim => "verbatim",
+            DataType::Uri => "uri",
+            DataType::Keyword => "keyword",
+            DataType::Option => "option",
+            DataType::Range => "range",
+            DataType::Code => "code",
+        }
+    }
+}
+
+impl ConstraintType {
+
+    fn name(self) -> &'static str {
+        match self {
+            ConstraintType::Mandatory => "mandatory",
+            ConstraintType::Data => "data",
+            ConstraintType::Conditional => "conditional",
+        }
+    }
+}
+
+impl Quantifier {
+
+    /// Name as used in the control file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Quantifier::All => "all",
+            Quantifier::One => "one",
+            Quantifier::None => "none",
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::Write;
+use serde::Deserialize;
+use quick_xml::Writer;
+
+use super::xml;
+
+
+/// `bcf:inheritance`: `\DefaultInheritance` and `\DeclareDataInheritance`.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct Inheritance {
+    #[serde(default)] pub defaults: Option<InheritanceDefaults>,
+    #[serde(default)] pub inherit: Vec<Inherit>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct InheritanceDefaults {
+    pub inherit_all: bool,
+    pub override_target: bool,
+    #[serde(default)] pub type_pair: Vec<TypePair>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TypePair {
+    pub source: String,
+    pub target: String,
+    /// Only set for the exceptions in [`InheritanceDefaults`].
+    #[serde(default)] pub inherit_all: Option<bool>,
+    #[serde(default)] pub override_target: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Inherit {
+    #[serde(default)] pub type_pair: Vec<TypePair>,
+    #[serde(default)] pub field: Vec<InheritField>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct InheritField {
+    pub source: String,
+    #[serde(default)] pub target: Option<String>,
+    #[serde(default)] pub skip: Option<bool>,
+    #[serde(default)] pub override_target: Option<bool>,
+}
+
+
+// XML output
+// ----------
+
+impl Inheritance {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:inheritance", &[])?;
+        if let Some(defaults) = &self.defaults {
+            let attributes = [
+                ("inherit_all", xml::attr(defaults.inherit_all)),
+                ("override_target", xml::attr(defaults.override_target)),
+            ];
+            xml::start(writer, "bcf:defaults", &attributes)?;
+            write_type_pairs(writer, &defaults.type_pair)?;
+            xml::end(writer, "bcf:defaults")?;
+        }
+        for inherit in &self.inherit {
+            xml::start(writer, "bcf:inherit", &[])?;
+            write_type_pairs(writer, &inherit.type_pair)?;
+            for field in &inherit.field {
+                let attributes = [
+                    ("source", xml::attr(&field.source)),
+                    ("target", xml::opt(&field.target)),
+                    ("skip", xml::opt(&field.skip)),
+                    ("override_target", xml::opt(&field.override_target)),
+                ];
+                xml::empty(writer, "bcf:field", &attributes)?;
+            }
+            xml::end(writer, "bcf:inherit")?;
+        }
+        xml::end(writer, "bcf:inheritance")
+    }
+}
+
+fn write_type_pairs<W: Write>(writer: &mut Writer<W>, type_pairs: &[TypePair]) -> quick_xml::Result<()> {
+    for pair in type_pairs {
+        let attributes = [
+            ("source", xml::attr(&pair.source)),
+            ("target", xml::attr(&pair.target)),
+            ("inherit_all", xml::opt(&pair.inherit_all)),
+            ("override_target", xml::opt(&pair.override_target)),
+        ];
+        xml::empty(writer, "bcf:type_pair", &attributes)?;
+    }
+    Ok(())
+}
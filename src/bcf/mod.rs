@@ -0,0 +1,399 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use serde::Deserialize;
+use quick_xml::de::DeError;
+use quick_xml::events::{BytesDecl, Event};
+use quick_xml::{Reader, Writer};
+
+
+mod datamodel;
+mod inheritance;
+mod options;
+mod schema;
+mod section;
+mod sourcemap;
+mod templates;
+mod xml;
+
+pub use datamodel::{
+    Condition, Conflict, ConstantValue, Constraint, ConstraintItem, ConstraintType, DataType, Datamodel,
+    DatamodelBuilder, EntrytypeDefinition, FieldDefinition, FieldType, Fields, Mandatory, Quantifier,
+};
+pub use inheritance::{Inherit, InheritField, Inheritance, InheritanceDefaults, TypePair};
+pub use options::{
+    Component, DataFieldSet, DataFieldSetMember, OptionDatatype, OptionKind, OptionScope, OptionValue,
+    Options, OrderedValue, ScopeType, ScopedOption,
+};
+pub use section::{Bibdata, Citekey, Datalist, DatalistFilter, Datasource, Filter, FilterOr, Section};
+pub use sourcemap::{Map, MapStep, Maps, Sourcemap};
+pub use templates::{
+    ExtradateScope, ExtradateSpec, KeyPart, LabelElement, LabelPart, LabelalphaNameTemplate,
+    LabelalphaNamepart, LabelalphaTemplate, NameKeyPart, OrderedField, Presort, Sort, SortItem,
+    SortingNameKeyTemplate, SortingTemplate, UniquenameNamepart, UniquenameTemplate,
+};
+
+
+/// Contents of a `.bcf` file, written by biblatex for the backend. Elements
+/// that may be repeated are kept in document order. Read with
+/// [`Controlfile::from_str`], which checks the format version.
+#[derive(Debug, PartialEq)]
+pub struct Controlfile {
+    pub version: String,
+    pub bltxversion: String,
+    pub options: Vec<Options>,
+    pub optionscopes: Vec<OptionScope>,
+    pub datafieldsets: Vec<DataFieldSet>,
+    pub sourcemap: Option<Sourcemap>,
+    pub labelalphanametemplates: Vec<LabelalphaNameTemplate>,
+    pub labelalphatemplates: Vec<LabelalphaTemplate>,
+    pub extradatespec: Option<ExtradateSpec>,
+    pub inheritance: Option<Inheritance>,
+    pub uniquenametemplates: Vec<UniquenameTemplate>,
+    pub sortingnamekeytemplates: Vec<SortingNameKeyTemplate>,
+    pub presorts: Vec<Presort>,
+    pub datamodel: Datamodel,
+    pub bibdata: Vec<Bibdata>,
+    pub sections: Vec<Section>,
+    pub sortingtemplates: Vec<SortingTemplate>,
+    pub datalists: Vec<Datalist>,
+    /// Elements of newer biblatex versions.
+    pub unknown: Vec<UnknownElement>,
+}
+
+/// A top-level element without a typed representation, kept as read to be
+/// written back unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownElement {
+    pub name: String,
+    events: Vec<Event<'static>>,
+}
+
+impl Controlfile {
+
+    /// Reads a control file, see [`Controlfile::from_str`].
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let xml = std::fs::read_to_string(path).map_err(|err| DeError::from(quick_xml::Error::from(err)))?;
+        xml.parse()
+    }
+
+    /// Options of `component` for `type`, `global` or an entry type.
+    pub fn options(&self, component: Component, r#type: &str) -> Option<&Options> {
+        self.options.iter().find(|options| options.component == component && options.r#type == r#type)
+    }
+
+    pub fn optionscope(&self, r#type: ScopeType) -> Option<&OptionScope> {
+        self.optionscopes.iter().find(|scope| scope.r#type == r#type)
+    }
+
+    pub fn section(&self, number: u32) -> Option<&Section> {
+        self.sections.iter().find(|section| section.number == number)
+    }
+
+    pub fn bibdata(&self, section: u32) -> Option<&Bibdata> {
+        self.bibdata.iter().find(|bibdata| bibdata.section == section)
+    }
+
+    pub fn sortingtemplate(&self, name: &str) -> Option<&SortingTemplate> {
+        self.sortingtemplates.iter().find(|template| template.name == name)
+    }
+
+    pub fn to_file<P: AsRef<std::path::Path>>(&self, path: P) -> quick_xml::Result<()> {
+        let file = std::fs::File::create(path).map_err(quick_xml::Error::from)?;
+        let mut writer = Writer::new_with_indent(std::io::BufWriter::new(file), b' ', 2);
+        self.write(&mut writer)?;
+        writer.inner().flush().map_err(quick_xml::Error::from)
+    }
+
+    /// Writes the control file in the element order of biblatex, with the
+    /// `bcf:bibdata` and `bcf:section` of each refsection together. Unknown
+    /// elements come last.
+    pub fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        writer.write_event(Event::Decl(BytesDecl::new(b"1.0", Some(b"UTF-8"), None)))?;
+        let attributes = [
+            ("version", xml::attr(&self.version)),
+            ("bltxversion", xml::attr(&self.bltxversion)),
+            ("xmlns:bcf", xml::attr(NAMESPACE)),
+        ];
+        xml::start(writer, "bcf:controlfile", &attributes)?;
+        for options in &self.options {
+            options.write(writer)?;
+        }
+        for scope in &self.optionscopes {
+            scope.write(writer)?;
+        }
+        for set in &self.datafieldsets {
+            set.write(writer)?;
+        }
+        if let Some(sourcemap) = &self.sourcemap {
+            sourcemap.write(writer)?;
+        }
+        for template in &self.labelalphanametemplates {
+            template.write(writer)?;
+        }
+        for template in &self.labelalphatemplates {
+            template.write(writer)?;
+        }
+        if let Some(spec) = &self.extradatespec {
+            spec.write(writer)?;
+        }
+        if let Some(inheritance) = &self.inheritance {
+            inheritance.write(writer)?;
+        }
+        for template in &self.uniquenametemplates {
+            template.write(writer)?;
+        }
+        for template in &self.sortingnamekeytemplates {
+            template.write(writer)?;
+        }
+        for presort in &self.presorts {
+            presort.write(writer)?;
+        }
+        self.datamodel.write(writer)?;
+
+        let mut sections: Vec<u32> = self.bibdata.iter().map(|bibdata| bibdata.section)
+            .chain(self.sections.iter().map(|section| section.number))
+            .collect();
+        sections.sort_unstable();
+        sections.dedup();
+        for number in sections {
+            for bibdata in self.bibdata.iter().filter(|bibdata| bibdata.section == number) {
+                bibdata.write(writer)?;
+            }
+            for section in self.sections.iter().filter(|section| section.number == number) {
+                section.write(writer)?;
+            }
+        }
+
+        for template in &self.sortingtemplates {
+            template.write(writer)?;
+        }
+        for datalist in &self.datalists {
+            datalist.write(writer)?;
+        }
+        for element in &self.unknown {
+            for event in &element.events {
+                writer.write_event(event)?;
+            }
+        }
+        xml::end(writer, "bcf:controlfile")
+    }
+}
+
+/// Reads a control file after checking that its format is supported, with
+/// the elements unknown to this crate.
+impl FromStr for Controlfile {
+    type Err = Error;
+
+    fn from_str(xml: &str) -> Result<Self, Error> {
+        let header: Header = quick_xml::de::from_str(xml)?;
+        if !is_supported(&header.version, &header.bltxversion) {
+            return Err(Error::UnsupportedVersion(header.version, header.bltxversion));
+        }
+        let elements: Elements = quick_xml::de::from_str(xml)?;
+        let mut controlfile = Controlfile::try_from(elements).map_err(DeError::Custom)?;
+        controlfile.unknown = unknown_elements(xml).map_err(DeError::from)?;
+        Ok(controlfile)
+    }
+}
+
+const NAMESPACE: &str = "https://sourceforge.net/projects/biblatex";
+
+
+// Versions
+// --------
+
+/// Oldest and newest control file format (`version`) that can be read.
+pub const SUPPORTED_VERSIONS: (&str, &str) = ("3.7", "3.10");
+/// Oldest biblatex (`bltxversion`) that can have written a control file.
+pub const MIN_BLTXVERSION: &str = "3.16";
+
+#[derive(Debug)]
+pub enum Error {
+    /// Not a well-formed control file.
+    Xml(DeError),
+    /// `version` and `bltxversion` of a control file in an unsupported format.
+    UnsupportedVersion(String, String),
+}
+
+impl From<DeError> for Error {
+    fn from(err: DeError) -> Self {
+        Error::Xml(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Xml(err) => write!(f, "invalid control file: {}", err),
+            Error::UnsupportedVersion(version, bltxversion) => write!(
+                f, "unsupported control file version '{}' written by biblatex '{}', supported are versions {} to {} from biblatex {} on",
+                version, bltxversion, SUPPORTED_VERSIONS.0, SUPPORTED_VERSIONS.1, MIN_BLTXVERSION,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The attributes of `bcf:controlfile`, which are checked before the rest.
+#[derive(Deserialize)]
+struct Header {
+    #[serde(default)] version: String,
+    #[serde(default)] bltxversion: String,
+}
+
+/// Unreadable versions count as `0.0`, and so as unsupported.
+fn is_supported(version: &str, bltxversion: &str) -> bool {
+    let parse = |text| parse_version(text).unwrap_or_default();
+    (parse(SUPPORTED_VERSIONS.0)..=parse(SUPPORTED_VERSIONS.1)).contains(&parse(version))
+        && parse(bltxversion) >= parse(MIN_BLTXVERSION)
+}
+
+/// Major and minor number of versions like `3.16` or `3.7a`.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.trim().split_once('.')?;
+    let minor = &minor[..minor.find(|c: char| !c.is_ascii_digit()).unwrap_or(minor.len())];
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+
+// Deserialization
+// ---------------
+
+// Sections repeat `bcf:bibdata` and `bcf:section` for each refsection, which
+// quick-xml can only read as a sequence of differently tagged elements.
+
+#[derive(Deserialize)]
+struct Elements {
+    #[serde(default)] version: String,
+    #[serde(default)] bltxversion: String,
+    #[serde(rename = "$value", default)] elements: Vec<Element>,
+}
+
+#[derive(Deserialize)]
+enum Element {
+    #[serde(rename = "bcf:options")] Options(Options),
+    #[serde(rename = "bcf:optionscope")] OptionScope(OptionScope),
+    #[serde(rename = "bcf:datafieldset")] DataFieldSet(DataFieldSet),
+    #[serde(rename = "bcf:sourcemap")] Sourcemap(Sourcemap),
+    #[serde(rename = "bcf:labelalphanametemplate")] LabelalphaNameTemplate(LabelalphaNameTemplate),
+    #[serde(rename = "bcf:labelalphatemplate")] LabelalphaTemplate(LabelalphaTemplate),
+    #[serde(rename = "bcf:extradatespec")] ExtradateSpec(ExtradateSpec),
+    #[serde(rename = "bcf:inheritance")] Inheritance(Inheritance),
+    #[serde(rename = "bcf:uniquenametemplate")] UniquenameTemplate(UniquenameTemplate),
+    #[serde(rename = "bcf:sortingnamekeytemplate")] SortingNameKeyTemplate(SortingNameKeyTemplate),
+    #[serde(rename = "bcf:presort")] Presort(Presort),
+    #[serde(rename = "bcf:datamodel")] Datamodel(Box<Datamodel>),
+    #[serde(rename = "bcf:bibdata")] Bibdata(Bibdata),
+    #[serde(rename = "bcf:section")] Section(Section),
+    #[serde(rename = "bcf:sortingtemplate")] SortingTemplate(SortingTemplate),
+    #[serde(rename = "bcf:datalist")] Datalist(Datalist),
+    /// Elements without a typed representation, see [`unknown_elements`].
+    #[serde(other)] Other,
+}
+
+impl TryFrom<Elements> for Controlfile {
+    type Error = String;
+
+    fn try_from(mut raw: Elements) -> Result<Self, Self::Error> {
+        let datamodel = raw.elements.iter()
+            .position(|element| matches!(element, Element::Datamodel(_)))
+            .map(|pos| raw.elements.remove(pos));
+        let Some(Element::Datamodel(datamodel)) = datamodel else {
+            return Err("missing bcf:datamodel".to_owned());
+        };
+        let mut controlfile = Controlfile {
+            version: raw.version,
+            bltxversion: raw.bltxversion,
+            options: Vec::new(),
+            optionscopes: Vec::new(),
+            datafieldsets: Vec::new(),
+            sourcemap: None,
+            labelalphanametemplates: Vec::new(),
+            labelalphatemplates: Vec::new(),
+            extradatespec: None,
+            inheritance: None,
+            uniquenametemplates: Vec::new(),
+            sortingnamekeytemplates: Vec::new(),
+            presorts: Vec::new(),
+            datamodel: *datamodel,
+            bibdata: Vec::new(),
+            sections: Vec::new(),
+            sortingtemplates: Vec::new(),
+            datalists: Vec::new(),
+            unknown: Vec::new(),
+        };
+        for element in raw.elements {
+            match element {
+                Element::Options(options) => controlfile.options.push(options),
+                Element::OptionScope(scope) => controlfile.optionscopes.push(scope),
+                Element::DataFieldSet(set) => controlfile.datafieldsets.push(set),
+                Element::Sourcemap(sourcemap) => controlfile.sourcemap = Some(sourcemap),
+                Element::LabelalphaNameTemplate(template) => controlfile.labelalphanametemplates.push(template),
+                Element::LabelalphaTemplate(template) => controlfile.labelalphatemplates.push(template),
+                Element::ExtradateSpec(spec) => controlfile.extradatespec = Some(spec),
+                Element::Inheritance(inheritance) => controlfile.inheritance = Some(inheritance),
+                Element::UniquenameTemplate(template) => controlfile.uniquenametemplates.push(template),
+                Element::SortingNameKeyTemplate(template) => controlfile.sortingnamekeytemplates.push(template),
+                Element::Presort(presort) => controlfile.presorts.push(presort),
+                Element::Datamodel(_) => return Err("duplicate bcf:datamodel".to_owned()),
+                Element::Bibdata(bibdata) => controlfile.bibdata.push(bibdata),
+                Element::Section(section) => controlfile.sections.push(section),
+                Element::SortingTemplate(template) => controlfile.sortingtemplates.push(template),
+                Element::Datalist(datalist) => controlfile.datalists.push(datalist),
+                Element::Other => (),
+            }
+        }
+        Ok(controlfile)
+    }
+}
+
+/// Names of the variants of [`Element`].
+const ELEMENTS: [&str; 16] = [
+    "bcf:options", "bcf:optionscope", "bcf:datafieldset", "bcf:sourcemap", "bcf:labelalphanametemplate",
+    "bcf:labelalphatemplate", "bcf:extradatespec", "bcf:inheritance", "bcf:uniquenametemplate",
+    "bcf:sortingnamekeytemplate", "bcf:presort", "bcf:datamodel", "bcf:bibdata", "bcf:section",
+    "bcf:sortingtemplate", "bcf:datalist",
+];
+
+/// The top-level elements serde skips as [`Element::Other`], read again as
+/// events since quick-xml cannot deserialize arbitrary elements.
+fn unknown_elements(xml: &str) -> quick_xml::Result<Vec<UnknownElement>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut elements = Vec::new();
+    let mut current: Option<UnknownElement> = None;
+    let mut depth = 0;
+    let mut buf = Vec::new();
+    loop {
+        let event = reader.read_event(&mut buf)?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(tag) | Event::Empty(tag) if depth == 1 => {
+                let name = reader.decode(tag.name())?;
+                if !ELEMENTS.contains(&name) {
+                    current = Some(UnknownElement { name: name.to_owned(), events: Vec::new() });
+                }
+            },
+            _ => (),
+        }
+        match &event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            _ => (),
+        }
+        if let Some(element) = &mut current {
+            element.events.push(event.into_owned());
+            if depth == 1 {
+                elements.extend(current.take());
+            }
+        }
+        buf.clear();
+    }
+    Ok(elements)
+}
+
+
+#[cfg(test)]
+mod tests;
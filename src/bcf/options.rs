@@ -0,0 +1,197 @@
+use std::io::Write;
+use serde::Deserialize;
+use quick_xml::Writer;
+
+use super::xml;
+
+
+/// `bcf:options`: option values for biber, or for biblatex globally or per
+/// entry type (`type` is `global` or the entry type).
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Options {
+    pub component: Component,
+    pub r#type: String,
+    #[serde(default)] pub option: Vec<OptionValue>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Component {
+    Biber,
+    Biblatex,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct OptionValue {
+    pub r#type: OptionKind,
+    pub key: String,
+    #[serde(default)] pub value: Vec<OrderedValue>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionKind {
+    Singlevalued,
+    Multivalued,
+}
+
+/// Value of a multivalued option or other ordered lists.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct OrderedValue {
+    #[serde(default)] pub order: Option<u32>,
+    #[serde(rename = "$value", default)] pub value: String,
+}
+
+/// `bcf:optionscope`: where options may be set and their datatype.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct OptionScope {
+    pub r#type: ScopeType,
+    #[serde(default)] pub option: Vec<ScopedOption>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ScopeType {
+    Global,
+    Entrytype,
+    Entry,
+    Namelist,
+    Name,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ScopedOption {
+    pub datatype: OptionDatatype,
+    /// Name of the option as seen by biber, if it differs.
+    #[serde(default)] pub backendin: Option<String>,
+    #[serde(rename = "$value")] pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionDatatype {
+    Boolean,
+    Integer,
+    String,
+    Xml,
+}
+
+/// `bcf:datafieldset`: named set of fields for source mapping.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct DataFieldSet {
+    pub name: String,
+    #[serde(default)] pub member: Vec<DataFieldSetMember>,
+}
+
+/// Either a single `field` or all fields of a `fieldtype` and `datatype`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct DataFieldSetMember {
+    #[serde(default)] pub field: Option<String>,
+    #[serde(default)] pub fieldtype: Option<String>,
+    #[serde(default)] pub datatype: Option<String>,
+}
+
+
+impl Options {
+
+    /// Values of the option `key`, in order.
+    pub fn get(&self, key: &str) -> Option<Vec<&str>> {
+        let option = self.option.iter().find(|option| option.key == key)?;
+        let mut values: Vec<&OrderedValue> = option.value.iter().collect();
+        values.sort_by_key(|value| value.order);
+        Some(values.into_iter().map(|value| value.value.as_str()).collect())
+    }
+}
+
+
+// XML output
+// ----------
+
+impl Options {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:options", &[("component", xml::attr(self.component.name())), ("type", xml::attr(&self.r#type))])?;
+        for option in &self.option {
+            xml::start(writer, "bcf:option", &[("type", xml::attr(option.r#type.name()))])?;
+            xml::text(writer, "bcf:key", &[], &option.key)?;
+            for value in &option.value {
+                xml::text(writer, "bcf:value", &[("order", xml::opt(&value.order))], &value.value)?;
+            }
+            xml::end(writer, "bcf:option")?;
+        }
+        xml::end(writer, "bcf:options")
+    }
+}
+
+impl OptionScope {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:optionscope", &[("type", xml::attr(self.r#type.name()))])?;
+        for option in &self.option {
+            let attributes = [("datatype", xml::attr(option.datatype.name())), ("backendin", xml::opt(&option.backendin))];
+            xml::text(writer, "bcf:option", &attributes, &option.name)?;
+        }
+        xml::end(writer, "bcf:optionscope")
+    }
+}
+
+impl DataFieldSet {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:datafieldset", &[("name", xml::attr(&self.name))])?;
+        for member in &self.member {
+            let attributes = [
+                ("field", xml::opt(&member.field)),
+                ("fieldtype", xml::opt(&member.fieldtype)),
+                ("datatype", xml::opt(&member.datatype)),
+            ];
+            xml::empty(writer, "bcf:member", &attributes)?;
+        }
+        xml::end(writer, "bcf:datafieldset")
+    }
+}
+
+impl Component {
+
+    fn name(self) -> &'static str {
+        match self {
+            Component::Biber => "biber",
+            Component::Biblatex => "biblatex",
+        }
+    }
+}
+
+impl OptionKind {
+
+    fn name(self) -> &'static str {
+        match self {
+            OptionKind::Singlevalued => "singlevalued",
+            OptionKind::Multivalued => "multivalued",
+        }
+    }
+}
+
+impl ScopeType {
+
+    fn name(self) -> &'static str {
+        match self {
+            ScopeType::Global => "GLOBAL",
+            ScopeType::Entrytype => "ENTRYTYPE",
+            ScopeType::Entry => "ENTRY",
+            ScopeType::Namelist => "NAMELIST",
+            ScopeType::Name => "NAME",
+        }
+    }
+}
+
+impl OptionDatatype {
+
+    fn name(self) -> &'static str {
+        match self {
+            OptionDatatype::Boolean => "boolean",
+            OptionDatatype::Integer => "integer",
+            OptionDatatype::String => "string",
+            OptionDatatype::Xml => "xml",
+        }
+    }
+}
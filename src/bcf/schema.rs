@@ -0,0 +1,181 @@
+use serde_json::{json, Map, Value};
+
+use super::{Condition, ConstraintItem, ConstraintType, DataType, Datamodel, FieldType, Mandatory, Quantifier};
+
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// All datatypes, which each have a value schema.
+const DATATYPES: [DataType; 13] = [
+    DataType::Name, DataType::Literal, DataType::Integer, DataType::Key, DataType::Entrykey, DataType::Date,
+    DataType::Datepart, DataType::Verbatim, DataType::Uri, DataType::Keyword, DataType::Option, DataType::Range,
+    DataType::Code,
+];
+
+impl Datamodel {
+
+    /// JSON Schema of a bibliography: an array of entries like
+    /// `{"key": "knuth84", "entrytype": "book", "fields": {"title": "..."}}`.
+    /// Each entry type has a sub-schema with its allowed fields and
+    /// `mandatory`, `data` and `conditional` constraints, each datatype one for
+    /// its values, e.g. names as objects of their `nameparts`.
+    pub fn json_schema(&self) -> Value {
+        let mut defs = Map::new();
+        let entrytypes: Vec<&str> = self.entrytypes().map(|def| def.name()).collect();
+        defs.insert("entry".to_owned(), json!({
+            "type": "object",
+            "required": ["key", "entrytype", "fields"],
+            "properties": {
+                "key": { "type": "string" },
+                "entrytype": { "enum": entrytypes },
+            },
+            "oneOf": entrytypes.iter().map(|name| reference(&format!("entrytype-{}", name))).collect::<Vec<_>>(),
+        }));
+        for entrytype in &entrytypes {
+            defs.insert(format!("entrytype-{}", entrytype), self.entrytype_schema(entrytype));
+        }
+        for datatype in DATATYPES {
+            defs.insert(format!("datatype-{}", datatype.name()), self.datatype_schema(datatype));
+        }
+        json!({
+            "$schema": DRAFT,
+            "type": "array",
+            "items": reference("entry"),
+            "$defs": defs,
+        })
+    }
+
+    fn entrytype_schema(&self, entrytype: &str) -> Value {
+        let mut properties = Map::new();
+        for field in self.fields_for_entrytype(entrytype) {
+            properties.insert(field.clone(), self.field_schema(entrytype, field));
+        }
+        // dates are allowed by their year, as biber splits them into parts
+        for def in self.fields().filter(|def| def.datatype == DataType::Date) {
+            let year = def.name.strip_suffix("date").map(|prefix| format!("{}year", prefix));
+            if year.is_some_and(|year| self.is_field_for_entrytype(entrytype, &year)) {
+                properties.insert(def.name.clone(), self.field_schema(entrytype, &def.name));
+            }
+        }
+
+        let mut required = Vec::new();
+        let mut all_of = Vec::new();
+        for mandatory in self.mandatory_fields(entrytype) {
+            match mandatory {
+                Mandatory::Field(field) => required.push(json!(field)),
+                Mandatory::AnyOf(fields) => all_of.push(json!({ "anyOf": present(fields) })),
+                Mandatory::OneOf(fields) => all_of.push(json!({ "oneOf": present(fields) })),
+            }
+        }
+        for constraint in self.constraints(entrytype).filter(|constraint| constraint.r#type == ConstraintType::Conditional) {
+            let condition = |wanted: fn(&ConstraintItem) -> Option<&Condition>| constraint.items.iter().find_map(wanted);
+            let antecedent = condition(|item| match item { ConstraintItem::Antecedent(c) => Some(c), _ => None });
+            let consequent = condition(|item| match item { ConstraintItem::Consequent(c) => Some(c), _ => None });
+            if let (Some(antecedent), Some(consequent)) = (antecedent, consequent) {
+                all_of.push(json!({ "if": quantified(antecedent), "then": quantified(consequent) }));
+            }
+        }
+
+        let mut fields = json!({
+            "type": "object",
+            "properties": properties,
+            "additionalProperties": false,
+            "required": required,
+        });
+        if !all_of.is_empty() {
+            fields["allOf"] = json!(all_of);
+        }
+        json!({
+            "type": "object",
+            "properties": {
+                "entrytype": { "const": entrytype },
+                "fields": fields,
+            },
+        })
+    }
+
+    /// Value schema of the datatype, refined by the `data` constraints on
+    /// `field` for `entrytype`. Fields unknown to the datamodel are literals.
+    fn field_schema(&self, entrytype: &str, field: &str) -> Value {
+        let (fieldtype, datatype, xsv) = match self.field(field) {
+            Some(def) => (def.fieldtype, def.datatype, def.format == "xsv"),
+            None => (FieldType::Field, DataType::Literal, false),
+        };
+        let value = reference(&format!("datatype-{}", datatype.name()));
+        let mut schema = match (fieldtype, datatype) {
+            (FieldType::List, _) => json!({ "type": "array", "items": value }),
+            // biber splits these itself
+            (_, DataType::Keyword | DataType::Option) => value,
+            _ if xsv => json!({ "type": "array", "items": value }),
+            _ => value,
+        };
+
+        let mut refinements = Vec::new();
+        let constraints: Vec<_> = self.constraints(entrytype)
+            .filter(|constraint| constraint.r#type == ConstraintType::Data)
+            .filter(|constraint| constraint.items.iter().any(|item| matches!(item, ConstraintItem::Field(name) if name == field)))
+            .collect();
+        // biblatex declares a pattern of the `gender` constant, which is used without it
+        if field == "gender" && constraints.is_empty() {
+            refinements.push(json!({ "enum": self.genders() }));
+        }
+        for constraint in constraints {
+            match constraint.datatype.as_deref().unwrap_or("") {
+                "integer" | "datepart" => {
+                    let mut range = json!({ "type": "integer" });
+                    if let Some(min) = constraint.rangemin {
+                        range["minimum"] = json!(min);
+                    }
+                    if let Some(max) = constraint.rangemax {
+                        range["maximum"] = json!(max);
+                    }
+                    refinements.push(range);
+                },
+                "pattern" => {
+                    let pattern = constraint.pattern.as_deref().unwrap_or("");
+                    refinements.push(json!({ "pattern": format!("^(?:{})$", pattern) }));
+                },
+                _ => (),
+            }
+        }
+        if !refinements.is_empty() {
+            refinements.insert(0, schema);
+            schema = json!({ "allOf": refinements });
+        }
+        schema
+    }
+
+    fn datatype_schema(&self, datatype: DataType) -> Value {
+        match datatype {
+            DataType::Name => {
+                let parts: Map<String, Value> = self.nameparts().iter()
+                    .map(|part| (part.clone(), json!({ "type": "string" })))
+                    .collect();
+                json!({ "type": "object", "properties": parts, "required": ["family"], "additionalProperties": false })
+            },
+            DataType::Integer | DataType::Datepart => json!({ "type": "integer" }),
+            DataType::Uri => json!({ "type": "string", "format": "iri" }),
+            DataType::Keyword => json!({ "type": "array", "items": { "type": "string" }, "uniqueItems": true }),
+            // `name=value` or a flag
+            DataType::Option => json!({ "type": "object", "additionalProperties": { "type": ["string", "integer", "boolean"] } }),
+            _ => json!({ "type": "string" }),
+        }
+    }
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+/// One schema per field, requiring it to be present.
+fn present(fields: &[String]) -> Vec<Value> {
+    fields.iter().map(|field| json!({ "required": [field] })).collect()
+}
+
+fn quantified(condition: &Condition) -> Value {
+    match condition.quant {
+        Quantifier::All => json!({ "required": condition.field }),
+        Quantifier::One => json!({ "anyOf": present(&condition.field) }),
+        Quantifier::None => json!({ "not": { "anyOf": present(&condition.field) } }),
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::Write;
+use serde::Deserialize;
+use quick_xml::Writer;
+
+use super::xml;
+
+
+/// `bcf:bibdata`: data sources of a refsection.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Bibdata {
+    pub section: u32,
+    #[serde(default)] pub datasource: Vec<Datasource>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Datasource {
+    /// `file`.
+    pub r#type: String,
+    /// `bibtex` or `biblatexml`.
+    pub datatype: String,
+    #[serde(default)] pub glob: Option<bool>,
+    #[serde(default)] pub encoding: Option<String>,
+    #[serde(rename = "$value")] pub path: String,
+}
+
+/// `bcf:section`: citations of a refsection. A key `*` stands for `\nocite{*}`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Section {
+    pub number: u32,
+    #[serde(default)] pub citekey: Vec<Citekey>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Citekey {
+    pub order: u32,
+    #[serde(default)] pub intorder: Option<u32>,
+    /// `set` for `\defbibentryset`.
+    #[serde(default)] pub r#type: Option<String>,
+    /// Comma separated members of a dynamic set.
+    #[serde(default)] pub members: Option<String>,
+    #[serde(rename = "$value")] pub key: String,
+}
+
+/// `bcf:datalist`: a sorted list of entries requested by the document.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Datalist {
+    pub section: u32,
+    pub name: String,
+    /// `entry` or `list`.
+    pub r#type: String,
+    #[serde(default)] pub sortingtemplatename: Option<String>,
+    #[serde(default)] pub sortingnamekeytemplatename: Option<String>,
+    #[serde(default)] pub labelprefix: Option<String>,
+    #[serde(default)] pub uniquenametemplatename: Option<String>,
+    #[serde(default)] pub labelalphanametemplatename: Option<String>,
+    #[serde(default)] pub namehashtemplatename: Option<String>,
+    #[serde(rename = "$value", default)] pub filters: Vec<DatalistFilter>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub enum DatalistFilter {
+    #[serde(rename = "bcf:filter")] Filter(Filter),
+    /// Matches if any of its filters does.
+    #[serde(rename = "bcf:filteror")] FilterOr(FilterOr),
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Filter {
+    /// `type`, `nottype`, `keyword`, `field`, ...
+    pub r#type: String,
+    #[serde(rename = "$value")] pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct FilterOr {
+    #[serde(default)] pub filter: Vec<Filter>,
+}
+
+
+impl Section {
+
+    /// Sets defined with `\defbibentryset`, with their members.
+    pub fn dynamic_sets(&self) -> impl Iterator<Item = (&str, Vec<String>)> {
+        self.citekey.iter()
+            .filter(|citekey| citekey.r#type.as_deref() == Some("set"))
+            .map(|citekey| {
+                let members = citekey.members.as_deref().unwrap_or("")
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|member| !member.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                (citekey.key.as_str(), members)
+            })
+    }
+}
+
+
+// XML output
+// ----------
+
+impl Bibdata {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:bibdata", &[("section", xml::attr(self.section))])?;
+        for source in &self.datasource {
+            let attributes = [
+                ("type", xml::attr(&source.r#type)),
+                ("datatype", xml::attr(&source.datatype)),
+                ("glob", xml::opt(&source.glob)),
+                ("encoding", xml::opt(&source.encoding)),
+            ];
+            xml::text(writer, "bcf:datasource", &attributes, &source.path)?;
+        }
+        xml::end(writer, "bcf:bibdata")
+    }
+}
+
+impl Section {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:section", &[("number", xml::attr(self.number))])?;
+        for citekey in &self.citekey {
+            let attributes = [
+                ("order", xml::attr(citekey.order)),
+                ("intorder", xml::opt(&citekey.intorder)),
+                ("type", xml::opt(&citekey.r#type)),
+                ("members", xml::opt(&citekey.members)),
+            ];
+            xml::text(writer, "bcf:citekey", &attributes, &citekey.key)?;
+        }
+        xml::end(writer, "bcf:section")
+    }
+}
+
+impl Datalist {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        let attributes = [
+            ("section", xml::attr(self.section)),
+            ("name", xml::attr(&self.name)),
+            ("type", xml::attr(&self.r#type)),
+            ("sortingtemplatename", xml::opt(&self.sortingtemplatename)),
+            ("sortingnamekeytemplatename", xml::opt(&self.sortingnamekeytemplatename)),
+            // always written by biblatex, usually empty
+            ("labelprefix", Some(self.labelprefix.clone().unwrap_or_default())),
+            ("uniquenametemplatename", xml::opt(&self.uniquenametemplatename)),
+            ("labelalphanametemplatename", xml::opt(&self.labelalphanametemplatename)),
+            ("namehashtemplatename", xml::opt(&self.namehashtemplatename)),
+        ];
+        xml::start(writer, "bcf:datalist", &attributes)?;
+        for filter in &self.filters {
+            match filter {
+                DatalistFilter::Filter(filter) => filter.write(writer)?,
+                DatalistFilter::FilterOr(or) => {
+                    xml::start(writer, "bcf:filteror", &[])?;
+                    for filter in &or.filter {
+                        filter.write(writer)?;
+                    }
+                    xml::end(writer, "bcf:filteror")?;
+                },
+            }
+        }
+        xml::end(writer, "bcf:datalist")
+    }
+}
+
+impl Filter {
+
+    fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::text(writer, "bcf:filter", &[("type", xml::attr(&self.r#type))], &self.value)
+    }
+}
@@ -0,0 +1,130 @@
+use std::io::Write;
+use serde::Deserialize;
+use quick_xml::Writer;
+
+use super::xml;
+
+
+/// `bcf:sourcemap`: `\DeclareSourcemap` and the style and driver defaults.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct Sourcemap {
+    #[serde(default)] pub maps: Vec<Maps>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Maps {
+    /// `bibtex` or `biblatexml`.
+    pub datatype: String,
+    /// `user`, `style` or `driver`.
+    pub level: String,
+    #[serde(default)] pub map_overwrite: Option<bool>,
+    #[serde(default)] pub map: Vec<Map>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Map {
+    #[serde(default)] pub map_overwrite: Option<bool>,
+    #[serde(default)] pub map_foreach: Option<String>,
+    #[serde(default)] pub refsection: Option<u32>,
+    #[serde(default)] pub per_datasource: Vec<String>,
+    #[serde(default)] pub per_type: Vec<String>,
+    #[serde(default)] pub per_nottype: Vec<String>,
+    #[serde(default)] pub map_step: Vec<MapStep>,
+}
+
+/// `\step` of a map, all attributes of the biblatex schema.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MapStep {
+    pub map_type_source: Option<String>,
+    pub map_type_target: Option<String>,
+    pub map_field_source: Option<String>,
+    pub map_field_target: Option<String>,
+    pub map_match: Option<String>,
+    pub map_matchi: Option<String>,
+    pub map_notmatch: Option<String>,
+    pub map_notmatchi: Option<String>,
+    pub map_replace: Option<String>,
+    pub map_field_set: Option<String>,
+    pub map_field_value: Option<String>,
+    pub map_entry_new: Option<String>,
+    pub map_entry_newtype: Option<String>,
+    pub map_entrytarget: Option<String>,
+    pub map_entry_clone: Option<String>,
+    pub map_append: Option<bool>,
+    pub map_appendstrict: Option<bool>,
+    pub map_final: Option<bool>,
+    pub map_null: Option<bool>,
+    pub map_origfield: Option<bool>,
+    pub map_origfieldval: Option<bool>,
+    pub map_origentrytype: Option<bool>,
+    pub map_entry_null: Option<bool>,
+}
+
+
+// XML output
+// ----------
+
+impl Sourcemap {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:sourcemap", &[])?;
+        for maps in &self.maps {
+            let attributes = [
+                ("datatype", xml::attr(&maps.datatype)),
+                ("level", xml::attr(&maps.level)),
+                ("map_overwrite", xml::boolean(maps.map_overwrite)),
+            ];
+            xml::start(writer, "bcf:maps", &attributes)?;
+            for map in &maps.map {
+                map.write(writer)?;
+            }
+            xml::end(writer, "bcf:maps")?;
+        }
+        xml::end(writer, "bcf:sourcemap")
+    }
+}
+
+impl Map {
+
+    fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        let attributes = [
+            ("map_overwrite", xml::boolean(self.map_overwrite)),
+            ("map_foreach", xml::opt(&self.map_foreach)),
+            ("refsection", xml::opt(&self.refsection)),
+        ];
+        xml::start(writer, "bcf:map", &attributes)?;
+        xml::texts(writer, "bcf:per_datasource", &self.per_datasource)?;
+        xml::texts(writer, "bcf:per_type", &self.per_type)?;
+        xml::texts(writer, "bcf:per_nottype", &self.per_nottype)?;
+        for step in &self.map_step {
+            let attributes = [
+                ("map_type_source", xml::opt(&step.map_type_source)),
+                ("map_type_target", xml::opt(&step.map_type_target)),
+                ("map_field_source", xml::opt(&step.map_field_source)),
+                ("map_field_target", xml::opt(&step.map_field_target)),
+                ("map_match", xml::opt(&step.map_match)),
+                ("map_matchi", xml::opt(&step.map_matchi)),
+                ("map_notmatch", xml::opt(&step.map_notmatch)),
+                ("map_notmatchi", xml::opt(&step.map_notmatchi)),
+                ("map_replace", xml::opt(&step.map_replace)),
+                ("map_field_set", xml::opt(&step.map_field_set)),
+                ("map_field_value", xml::opt(&step.map_field_value)),
+                ("map_entry_new", xml::opt(&step.map_entry_new)),
+                ("map_entry_newtype", xml::opt(&step.map_entry_newtype)),
+                ("map_entrytarget", xml::opt(&step.map_entrytarget)),
+                ("map_entry_clone", xml::opt(&step.map_entry_clone)),
+                ("map_append", xml::boolean(step.map_append)),
+                ("map_appendstrict", xml::boolean(step.map_appendstrict)),
+                ("map_final", xml::boolean(step.map_final)),
+                ("map_null", xml::boolean(step.map_null)),
+                ("map_origfield", xml::boolean(step.map_origfield)),
+                ("map_origfieldval", xml::boolean(step.map_origfieldval)),
+                ("map_origentrytype", xml::boolean(step.map_origentrytype)),
+                ("map_entry_null", xml::boolean(step.map_entry_null)),
+            ];
+            xml::empty(writer, "bcf:map_step", &attributes)?;
+        }
+        xml::end(writer, "bcf:map")
+    }
+}
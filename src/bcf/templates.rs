@@ -0,0 +1,307 @@
+use std::io::Write;
+use serde::Deserialize;
+use quick_xml::Writer;
+
+use super::xml;
+
+
+// Label templates
+// ---------------
+
+/// `bcf:labelalphanametemplate`: name parts used in alphabetic labels.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LabelalphaNameTemplate {
+    pub name: String,
+    #[serde(default)] pub namepart: Vec<LabelalphaNamepart>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LabelalphaNamepart {
+    pub order: u32,
+    #[serde(default)] pub r#use: Option<bool>,
+    #[serde(default)] pub pre: Option<bool>,
+    #[serde(default)] pub substring_width: Option<u32>,
+    #[serde(default)] pub substring_compound: Option<bool>,
+    #[serde(default)] pub substring_side: Option<String>,
+    #[serde(rename = "$value")] pub namepart: String,
+}
+
+/// `bcf:labelalphatemplate`: `\DeclareLabelalphaTemplate` for an entry type
+/// or `global`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LabelalphaTemplate {
+    pub r#type: String,
+    #[serde(default)] pub labelelement: Vec<LabelElement>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LabelElement {
+    pub order: u32,
+    #[serde(default)] pub labelpart: Vec<LabelPart>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LabelPart {
+    #[serde(rename = "final", default)] pub is_final: Option<bool>,
+    #[serde(default)] pub pad_char: Option<String>,
+    #[serde(default)] pub pad_side: Option<String>,
+    #[serde(default)] pub substring_width: Option<String>,
+    #[serde(default)] pub substring_width_max: Option<u32>,
+    #[serde(default)] pub substring_fixed_threshold: Option<u32>,
+    #[serde(default)] pub substring_side: Option<String>,
+    #[serde(default)] pub ifnames: Option<String>,
+    #[serde(default)] pub names: Option<String>,
+    #[serde(default)] pub namessep: Option<String>,
+    #[serde(default)] pub noalphaothers: Option<bool>,
+    #[serde(rename = "$value")] pub field: String,
+}
+
+/// `bcf:extradatespec`: fields compared for `extradate`, by scope.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct ExtradateSpec {
+    #[serde(default)] pub scope: Vec<ExtradateScope>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ExtradateScope {
+    #[serde(default)] pub field: Vec<OrderedField>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct OrderedField {
+    pub order: u32,
+    #[serde(rename = "$value")] pub field: String,
+}
+
+
+// Name templates
+// --------------
+
+/// `bcf:uniquenametemplate`: name parts used to disambiguate names.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct UniquenameTemplate {
+    pub name: String,
+    #[serde(default)] pub namepart: Vec<UniquenameNamepart>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct UniquenameNamepart {
+    pub order: u32,
+    #[serde(default)] pub r#use: Option<bool>,
+    #[serde(default)] pub base: Option<bool>,
+    /// `none`, `init`, `initorfull` or `full`.
+    #[serde(default)] pub disambiguation: Option<String>,
+    #[serde(rename = "$value")] pub namepart: String,
+}
+
+/// `bcf:sortingnamekeytemplate`: how names are turned into sort keys.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct SortingNameKeyTemplate {
+    pub name: String,
+    #[serde(default)] pub visibility: Option<String>,
+    #[serde(default)] pub keypart: Vec<KeyPart>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct KeyPart {
+    pub order: u32,
+    #[serde(default)] pub part: Vec<NameKeyPart>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct NameKeyPart {
+    /// `namepart` or `literal`.
+    pub r#type: String,
+    pub order: u32,
+    #[serde(default)] pub r#use: Option<bool>,
+    #[serde(default)] pub inits: Option<bool>,
+    #[serde(rename = "$value")] pub value: String,
+}
+
+
+// Sorting
+// -------
+
+/// `bcf:sortingtemplate`: `\DeclareSortingTemplate`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct SortingTemplate {
+    pub name: String,
+    #[serde(default)] pub locale: Option<String>,
+    #[serde(default)] pub sort: Vec<Sort>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Sort {
+    pub order: u32,
+    #[serde(rename = "final", default)] pub is_final: Option<bool>,
+    #[serde(default)] pub sort_direction: Option<String>,
+    #[serde(default)] pub sortcase: Option<bool>,
+    #[serde(default)] pub sortupper: Option<bool>,
+    #[serde(default)] pub locale: Option<String>,
+    #[serde(default)] pub sortitem: Vec<SortItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct SortItem {
+    pub order: u32,
+    #[serde(default)] pub substring_side: Option<String>,
+    #[serde(default)] pub substring_width: Option<u32>,
+    #[serde(default)] pub pad_side: Option<String>,
+    #[serde(default)] pub pad_width: Option<u32>,
+    #[serde(default)] pub pad_char: Option<String>,
+    #[serde(default)] pub literal: Option<bool>,
+    #[serde(rename = "$value")] pub field: String,
+}
+
+/// `bcf:presort`: `\DeclarePresort`, sorted on before all other sort keys,
+/// for an entry type or all of them.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Presort {
+    #[serde(default)] pub r#type: Option<String>,
+    #[serde(rename = "$value", default)] pub presort: String,
+}
+
+
+// XML output
+// ----------
+
+impl LabelalphaNameTemplate {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:labelalphanametemplate", &[("name", xml::attr(&self.name))])?;
+        for part in &self.namepart {
+            let attributes = [
+                ("order", xml::attr(part.order)),
+                ("use", xml::boolean(part.r#use)),
+                ("pre", xml::boolean(part.pre)),
+                ("substring_width", xml::opt(&part.substring_width)),
+                ("substring_compound", xml::boolean(part.substring_compound)),
+                ("substring_side", xml::opt(&part.substring_side)),
+            ];
+            xml::text(writer, "bcf:namepart", &attributes, &part.namepart)?;
+        }
+        xml::end(writer, "bcf:labelalphanametemplate")
+    }
+}
+
+impl LabelalphaTemplate {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:labelalphatemplate", &[("type", xml::attr(&self.r#type))])?;
+        for element in &self.labelelement {
+            xml::start(writer, "bcf:labelelement", &[("order", xml::attr(element.order))])?;
+            for part in &element.labelpart {
+                let attributes = [
+                    ("final", xml::boolean(part.is_final)),
+                    ("pad_char", xml::opt(&part.pad_char)),
+                    ("pad_side", xml::opt(&part.pad_side)),
+                    ("substring_width", xml::opt(&part.substring_width)),
+                    ("substring_width_max", xml::opt(&part.substring_width_max)),
+                    ("substring_fixed_threshold", xml::opt(&part.substring_fixed_threshold)),
+                    ("substring_side", xml::opt(&part.substring_side)),
+                    ("ifnames", xml::opt(&part.ifnames)),
+                    ("names", xml::opt(&part.names)),
+                    ("namessep", xml::opt(&part.namessep)),
+                    ("noalphaothers", xml::boolean(part.noalphaothers)),
+                ];
+                xml::text(writer, "bcf:labelpart", &attributes, &part.field)?;
+            }
+            xml::end(writer, "bcf:labelelement")?;
+        }
+        xml::end(writer, "bcf:labelalphatemplate")
+    }
+}
+
+impl ExtradateSpec {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:extradatespec", &[])?;
+        for scope in &self.scope {
+            xml::start(writer, "bcf:scope", &[])?;
+            for field in &scope.field {
+                xml::text(writer, "bcf:field", &[("order", xml::attr(field.order))], &field.field)?;
+            }
+            xml::end(writer, "bcf:scope")?;
+        }
+        xml::end(writer, "bcf:extradatespec")
+    }
+}
+
+impl UniquenameTemplate {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::start(writer, "bcf:uniquenametemplate", &[("name", xml::attr(&self.name))])?;
+        for part in &self.namepart {
+            let attributes = [
+                ("order", xml::attr(part.order)),
+                ("use", xml::boolean(part.r#use)),
+                ("base", xml::boolean(part.base)),
+                ("disambiguation", xml::opt(&part.disambiguation)),
+            ];
+            xml::text(writer, "bcf:namepart", &attributes, &part.namepart)?;
+        }
+        xml::end(writer, "bcf:uniquenametemplate")
+    }
+}
+
+impl SortingNameKeyTemplate {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        let attributes = [("name", xml::attr(&self.name)), ("visibility", xml::opt(&self.visibility))];
+        xml::start(writer, "bcf:sortingnamekeytemplate", &attributes)?;
+        for keypart in &self.keypart {
+            xml::start(writer, "bcf:keypart", &[("order", xml::attr(keypart.order))])?;
+            for part in &keypart.part {
+                let attributes = [
+                    ("type", xml::attr(&part.r#type)),
+                    ("order", xml::attr(part.order)),
+                    ("use", xml::boolean(part.r#use)),
+                    ("inits", xml::boolean(part.inits)),
+                ];
+                xml::text(writer, "bcf:part", &attributes, &part.value)?;
+            }
+            xml::end(writer, "bcf:keypart")?;
+        }
+        xml::end(writer, "bcf:sortingnamekeytemplate")
+    }
+}
+
+impl Presort {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        xml::text(writer, "bcf:presort", &[("type", xml::opt(&self.r#type))], &self.presort)
+    }
+}
+
+impl SortingTemplate {
+
+    pub(super) fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        let attributes = [("name", xml::attr(&self.name)), ("locale", xml::opt(&self.locale))];
+        xml::start(writer, "bcf:sortingtemplate", &attributes)?;
+        for sort in &self.sort {
+            let attributes = [
+                ("order", xml::attr(sort.order)),
+                ("final", xml::boolean(sort.is_final)),
+                ("sort_direction", xml::opt(&sort.sort_direction)),
+                ("sortcase", xml::boolean(sort.sortcase)),
+                ("sortupper", xml::boolean(sort.sortupper)),
+                ("locale", xml::opt(&sort.locale)),
+            ];
+            xml::start(writer, "bcf:sort", &attributes)?;
+            for item in &sort.sortitem {
+                let attributes = [
+                    ("order", xml::attr(item.order)),
+                    ("substring_side", xml::opt(&item.substring_side)),
+                    ("substring_width", xml::opt(&item.substring_width)),
+                    ("pad_side", xml::opt(&item.pad_side)),
+                    ("pad_width", xml::opt(&item.pad_width)),
+                    ("pad_char", xml::opt(&item.pad_char)),
+                    ("literal", xml::boolean(item.literal)),
+                ];
+                xml::text(writer, "bcf:sortitem", &attributes, &item.field)?;
+            }
+            xml::end(writer, "bcf:sort")?;
+        }
+        xml::end(writer, "bcf:sortingtemplate")
+    }
+}
@@ -0,0 +1,557 @@
+fn test_file(file: &str) -> std::path::PathBuf {
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("resources/test");
+    path.push(file);
+    path
+}
+
+/// The datamodel bundled as `Datamodel::default()`.
+fn default_datamodel_file() -> std::path::PathBuf {
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("resources/default-datamodel.bcf");
+    path
+}
+
+#[test]
+fn deserialize() {
+    let _test = super::Controlfile::from_file(default_datamodel_file()).unwrap();
+}
+
+#[test]
+fn default_datamodel() {
+    let controlfile = super::Controlfile::from_file(default_datamodel_file()).unwrap();
+    assert_eq!(51, controlfile.datamodel.valid_entrytypes().len());
+    assert_eq!(202, controlfile.datamodel.valid_fields().len());
+    assert!(controlfile.datamodel.valid_entrytypes().any(|entrytype| entrytype == "article"));
+    assert!(controlfile.datamodel.valid_fields().any(|field| field == "title"));
+}
+
+#[test]
+fn full_controlfile() {
+    use super::{Component, DatalistFilter, OptionDatatype, ScopeType};
+
+    let controlfile = super::Controlfile::from_file(test_file("full-controlfile.bcf")).unwrap();
+    assert_eq!("3.7", controlfile.version);
+    assert_eq!("3.16", controlfile.bltxversion);
+    assert_eq!(51, controlfile.datamodel.valid_entrytypes().len());
+
+    let biber = controlfile.options(Component::Biber, "global").unwrap();
+    assert_eq!(Some(vec!["2"]), biber.get("mincrossrefs"));
+    let biblatex = controlfile.options(Component::Biblatex, "global").unwrap();
+    assert_eq!(
+        Some(vec!["shortauthor", "author", "shorteditor", "editor", "translator"]),
+        biblatex.get("labelnamespec"),
+    );
+    assert_eq!(Some(vec!["1"]), controlfile.options(Component::Biblatex, "misc").unwrap().get("labeltitle"));
+    assert!(controlfile.options(Component::Biber, "misc").is_none());
+
+    assert_eq!(5, controlfile.optionscopes.len());
+    let entry = controlfile.optionscope(ScopeType::Entry).unwrap();
+    assert_eq!(OptionDatatype::Boolean, entry.option[0].datatype);
+    assert_eq!(Some("sortingnamekeytemplatename"), entry.option[4].backendin.as_deref());
+    assert_eq!(2, controlfile.datafieldsets.len());
+    assert_eq!(Some("list"), controlfile.datafieldsets[0].member[0].fieldtype.as_deref());
+
+    let sourcemap = controlfile.sourcemap.as_ref().unwrap();
+    assert_eq!(2, sourcemap.maps.len());
+    assert_eq!(vec!["examples.bib"], sourcemap.maps[0].map[0].per_datasource);
+    assert_eq!(Some(true), sourcemap.maps[0].map[0].map_step[1].map_append);
+    let driver = &sourcemap.maps[1];
+    assert_eq!("driver", driver.level);
+    assert_eq!(Some("thesis"), driver.map[2].map_step[0].map_type_target.as_deref());
+    assert_eq!(Some(true), driver.map[2].map_step[0].map_final);
+
+    let labelalpha = &controlfile.labelalphatemplates[0];
+    assert_eq!("global", labelalpha.r#type);
+    assert_eq!(Some(true), labelalpha.labelelement[0].labelpart[0].is_final);
+    assert_eq!("year", labelalpha.labelelement[1].labelpart[0].field);
+    assert_eq!(Some(1), controlfile.labelalphanametemplates[0].namepart[0].substring_width);
+    assert_eq!(2, controlfile.extradatespec.as_ref().unwrap().scope[0].field.len());
+    assert_eq!(
+        Some("initorfull"),
+        controlfile.uniquenametemplates[0].namepart[2].disambiguation.as_deref(),
+    );
+    assert_eq!(5, controlfile.sortingnamekeytemplates[0].keypart.len());
+
+    let inheritance = controlfile.inheritance.as_ref().unwrap();
+    assert!(inheritance.defaults.as_ref().unwrap().inherit_all);
+    assert_eq!(3, inheritance.inherit.len());
+    assert_eq!(Some(true), inheritance.inherit[1].field[4].skip);
+    let rules = crate::bibliography::Inheritance::from(inheritance);
+    assert!(!rules.defaults.type_pairs[0].inherit_all);
+    assert_eq!(6, rules.rules[0].type_pairs.len());
+    assert_eq!(Some(true), rules.rules[1].fields[0].override_target);
+    assert_eq!(None, rules.rules[1].fields[4].target);
+
+    assert_eq!(2, controlfile.bibdata.len());
+    assert_eq!(2, controlfile.sections.len());
+    assert_eq!(Some("biblatexml"), controlfile.bibdata(1).map(|bibdata| bibdata.datasource[1].datatype.as_str()));
+    assert_eq!("*", controlfile.section(1).unwrap().citekey[0].key);
+    let sets: Vec<_> = controlfile.section(0).unwrap().dynamic_sets().collect();
+    assert_eq!(vec![("knuth:ct:set", vec!["knuth:ct:a".to_owned(), "knuth:ct:b".to_owned()])], sets);
+
+    let nty = controlfile.sortingtemplate("nty").unwrap();
+    assert_eq!(6, nty.sort.len());
+    assert_eq!(Some(true), nty.sort[1].is_final);
+    assert_eq!(Some(4), nty.sort[5].sortitem[1].pad_width);
+    assert_eq!(Some("descending"), controlfile.sortingtemplate("ydnt").unwrap().sort[0].sort_direction.as_deref());
+
+    assert_eq!(2, controlfile.datalists.len());
+    assert!(controlfile.datalists[0].filters.is_empty());
+    let filters = &controlfile.datalists[1].filters;
+    assert!(matches!(&filters[0], DatalistFilter::Filter(filter) if filter.r#type == "field"));
+    assert!(matches!(&filters[1], DatalistFilter::FilterOr(or) if or.filter.len() == 2));
+}
+
+#[test]
+fn missing_datamodel() {
+    let xml = r#"<bcf:controlfile version="3.7" bltxversion="3.16"></bcf:controlfile>"#;
+    assert!(xml.parse::<super::Controlfile>().is_err());
+}
+
+#[test]
+fn versions() {
+    use super::{Controlfile, Error};
+
+    let original = std::fs::read_to_string(default_datamodel_file()).unwrap();
+    let with_versions = |version: &str, bltxversion: &str| {
+        original.replacen(r#"version="3.7" bltxversion="3.16""#, &format!(r#"version="{}" bltxversion="{}""#, version, bltxversion), 1)
+    };
+    assert!(with_versions("3.10", "3.20").parse::<Controlfile>().is_ok());
+    assert!(with_versions("3.8", "3.17a").parse::<Controlfile>().is_ok());
+
+    for (version, bltxversion) in [("3.5", "3.14"), ("3.11", "3.21"), ("3.7", "3.15"), ("", "3.16"), ("3.x", "3.16")] {
+        match with_versions(version, bltxversion).parse::<Controlfile>() {
+            Err(Error::UnsupportedVersion(v, b)) => assert_eq!((v.as_str(), b.as_str()), (version, bltxversion)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert_eq!(
+        with_versions("3.5", "3.14").parse::<Controlfile>().unwrap_err().to_string(),
+        "unsupported control file version '3.5' written by biblatex '3.14', supported are versions 3.7 to 3.10 from biblatex 3.16 on",
+    );
+    assert!(matches!(r#"<bcf:controlfile version="3.7" bltxversion="3.16"></bcf:controlfile>"#.parse::<Controlfile>(), Err(Error::Xml(_))));
+    assert!(matches!(Controlfile::from_file(test_file("missing.bcf")), Err(Error::Xml(_))));
+}
+
+/// Elements inside `root` with depth, sorted attributes and text.
+fn elements(xml: &str, root: &str) -> Vec<String> {
+    use quick_xml::events::{BytesStart, Event};
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    reader.trim_text(true);
+    let describe = |element: &BytesStart, depth: usize, reader: &quick_xml::Reader<&[u8]>| {
+        let mut attributes: Vec<String> = element.attributes()
+            .map(|attribute| {
+                let attribute = attribute.unwrap();
+                let value = attribute.unescape_and_decode_value(reader).unwrap();
+                format!("{}={}", String::from_utf8_lossy(attribute.key), value)
+            })
+            .collect();
+        attributes.sort();
+        format!("{} {} {}", depth, String::from_utf8_lossy(element.name()), attributes.join(" "))
+    };
+
+    let mut elements = Vec::new();
+    let mut depth = 0;
+    let mut inside = false;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            Event::Start(element) => {
+                inside |= element.name() == root.as_bytes();
+                if inside {
+                    elements.push(describe(&element, depth, &reader));
+                    depth += 1;
+                }
+            },
+            Event::Empty(element) if inside => elements.push(describe(&element, depth, &reader)),
+            Event::Text(text) if inside => {
+                let text = text.unescape_and_decode(&reader).unwrap();
+                elements.last_mut().unwrap().push_str(&format!(": {}", text));
+            },
+            Event::End(element) if inside => {
+                depth -= 1;
+                inside = element.name() != root.as_bytes();
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    elements
+}
+
+fn write_controlfile(datamodel: &super::Datamodel) -> String {
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write(br#"<bcf:controlfile version="3.7" bltxversion="3.16" xmlns:bcf="https://sourceforge.net/projects/biblatex">"#).unwrap();
+    datamodel.write(&mut writer).unwrap();
+    writer.write(b"</bcf:controlfile>").unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn datamodel_round_trip() {
+    let original = std::fs::read_to_string(default_datamodel_file()).unwrap();
+    let controlfile: super::Controlfile = original.parse().unwrap();
+    let written = write_controlfile(&controlfile.datamodel);
+    assert_eq!(elements(&original, "bcf:datamodel"), elements(&written, "bcf:datamodel"));
+    let reread: super::Controlfile = written.parse().unwrap();
+    assert_eq!(controlfile.datamodel, reread.datamodel);
+}
+
+#[test]
+fn constraints() {
+    use super::{ConstraintItem, ConstraintType, Quantifier};
+
+    let constraints = r#"
+    <bcf:constraints>
+      <bcf:entrytype>article</bcf:entrytype>
+      <bcf:constraint type="mandatory">
+        <bcf:field>title</bcf:field>
+        <bcf:fieldor><bcf:field>author</bcf:field><bcf:field>editor</bcf:field></bcf:fieldor>
+        <bcf:field>journaltitle</bcf:field>
+        <bcf:fieldxor><bcf:field>date</bcf:field><bcf:field>year</bcf:field></bcf:fieldxor>
+      </bcf:constraint>
+      <bcf:constraint type="data" datatype="integer" rangemin="1" rangemax="12" rangestart="a" rangeend="b">
+        <bcf:field>month</bcf:field>
+      </bcf:constraint>
+      <bcf:constraint type="conditional">
+        <bcf:antecedent quant="all"><bcf:field>eventyear</bcf:field></bcf:antecedent>
+        <bcf:consequent quant="one"><bcf:field>eventtitle</bcf:field><bcf:field>venue</bcf:field></bcf:consequent>
+      </bcf:constraint>
+    </bcf:constraints>
+  </bcf:datamodel>"#;
+    let original = std::fs::read_to_string(default_datamodel_file()).unwrap()
+        .replace("\n  </bcf:datamodel>", constraints);
+    let controlfile: super::Controlfile = original.parse().unwrap();
+    let article: Vec<_> = controlfile.datamodel.constraints("article").collect();
+    assert_eq!(article.len(), 1 + 1 + 4 + 3);
+
+    let mandatory = article[article.len() - 3];
+    let items: Vec<_> = mandatory.items.iter().map(|item| match item {
+        ConstraintItem::Field(field) => field.clone(),
+        ConstraintItem::FieldOr(group) => format!("or({})", group.field.join(",")),
+        ConstraintItem::FieldXor(group) => format!("xor({})", group.field.join(",")),
+        _ => panic!("unexpected {:?}", item),
+    }).collect();
+    assert_eq!(items, ["title", "or(author,editor)", "journaltitle", "xor(date,year)"]);
+
+    let data = article[article.len() - 2];
+    assert_eq!((data.rangemin, data.rangemax), (Some(1), Some(12)));
+    assert_eq!((data.rangestart.as_deref(), data.rangeend.as_deref()), (Some("a"), Some("b")));
+    let conditional = article[article.len() - 1];
+    assert_eq!(conditional.r#type, ConstraintType::Conditional);
+    assert!(matches!(&conditional.items[0], ConstraintItem::Antecedent(c) if c.quant == Quantifier::All));
+    assert!(matches!(&conditional.items[1], ConstraintItem::Consequent(c) if c.quant == Quantifier::One && c.field.len() == 2));
+
+    let written = write_controlfile(&controlfile.datamodel);
+    assert_eq!(elements(&original, "bcf:datamodel"), elements(&written, "bcf:datamodel"));
+}
+
+#[test]
+fn datamodel_queries() {
+    use super::{DataType, FieldType, Mandatory};
+
+    let datamodel = super::Controlfile::from_file(default_datamodel_file()).unwrap().datamodel;
+    assert_eq!(datamodel.entrytypes().next().unwrap().name(), "article");
+    assert!(datamodel.entrytype("xdata").unwrap().skip_output());
+    assert!(!datamodel.entrytype("book").unwrap().skip_output());
+    assert!(datamodel.entrytype("foo").is_none());
+
+    let article = datamodel.fields_for_entrytype("article");
+    assert_eq!(article[0], "abstract");
+    assert!(article.iter().any(|field| field == "journaltitle"));
+    assert!(!article.iter().any(|field| field == "isbn"));
+    assert!(datamodel.is_field_for_entrytype("article", "journaltitle"));
+    assert!(!datamodel.is_field_for_entrytype("book", "journaltitle"));
+    assert!(datamodel.is_field_for_entrytype("foo", "abstract"));
+    assert!(!datamodel.is_field_for_entrytype("foo", "journaltitle"));
+    assert!(datamodel.fields_for_entrytype("set").iter().any(|field| field == "entryset"));
+
+    assert_eq!(datamodel.mandatory_fields("article"), [
+        Mandatory::OneOf(vec!["date".into(), "year".into()]),
+        Mandatory::Field("author".into()),
+        Mandatory::Field("journaltitle".into()),
+        Mandatory::Field("title".into()),
+    ]);
+    assert_eq!(datamodel.mandatory_fields("booklet")[1], Mandatory::AnyOf(vec!["author".into(), "editor".into()]));
+    assert!(datamodel.mandatory_fields("foo").is_empty());
+
+    let author = datamodel.field("author").unwrap();
+    assert_eq!((author.fieldtype(), author.datatype()), (FieldType::List, DataType::Name));
+    assert!(!author.label());
+    assert!(datamodel.field("shortauthor").unwrap().label());
+    assert!(datamodel.field("year").unwrap().nullok());
+    assert!(datamodel.field("date").unwrap().skip_output());
+    assert_eq!(datamodel.field("keywords").unwrap().format(), "xsv");
+    assert!(datamodel.field("foo").is_none());
+    assert!(datamodel.is_multiscript("title"));
+    assert!(!datamodel.is_multiscript("date"));
+}
+
+#[test]
+fn datamodel_constants() {
+    let datamodel = super::Datamodel::default();
+    assert_eq!(datamodel.nameparts(), ["family", "given", "prefix", "suffix"]);
+    assert_eq!(datamodel.genders(), ["sf", "sm", "sn", "pf", "pm", "pn", "pp"]);
+    assert_eq!(datamodel.multiscript_forms()[1], "transliteration");
+    assert_eq!(datamodel.option_datatypes(), ["boolean", "integer", "string", "xml"]);
+    assert_eq!(datamodel.constant("foo"), None);
+
+    let xml = concat!(
+        "<bcf:controlfile version=\"3.7\" bltxversion=\"3.16\"><bcf:datamodel><bcf:constants>",
+        "<bcf:constant type=\"list\" name=\"nameparts\">family, given ,middle</bcf:constant>",
+        "<bcf:constant type=\"string\" name=\"note\">a, b</bcf:constant>",
+        "</bcf:constants><bcf:entrytypes/><bcf:fields/>",
+        "<bcf:entryfields><bcf:field>title</bcf:field></bcf:entryfields><bcf:multiscriptfields/>",
+        "</bcf:datamodel></bcf:controlfile>",
+    );
+    let controlfile: super::Controlfile = xml.parse().unwrap();
+    let datamodel = controlfile.datamodel;
+    assert_eq!(datamodel.nameparts(), ["family", "given", "middle"]);
+    assert_eq!(datamodel.constant("note"), Some(&super::ConstantValue::String("a, b".into())));
+    assert!(write_controlfile(&datamodel).contains("<bcf:constant type=\"list\" name=\"nameparts\">family,given,middle</bcf:constant>"));
+
+    let xml = xml.replace("\"string\"", "\"number\"");
+    assert!(xml.parse::<super::Controlfile>().is_err());
+}
+
+#[test]
+fn bundled_datamodel() {
+    let controlfile = super::Controlfile::from_file(default_datamodel_file()).unwrap();
+    let datamodel = super::Datamodel::default();
+    assert_eq!(controlfile.datamodel, datamodel);
+    assert!(datamodel.is_field_for_entrytype("article", "journaltitle"));
+}
+
+#[test]
+fn datamodel_builder() {
+    use super::{
+        Condition, ConstantValue, Constraint, ConstraintItem, DataType, DatamodelBuilder, FieldDefinition,
+        FieldType, Mandatory, Quantifier,
+    };
+
+    let mut builder = DatamodelBuilder::new();
+    builder
+        .constant("nameparts", ConstantValue::List(vec!["family".into(), "given".into()]))
+        .constant("title", ConstantValue::String("Notes".into()))
+        .entrytype("article", false)
+        .entrytype("note", false)
+        .entrytype("note", true)
+        .field(FieldDefinition::new("author", FieldType::List, DataType::Name))
+        .field(FieldDefinition::new("title", FieldType::Field, DataType::Literal))
+        .field(FieldDefinition::new("keywords", FieldType::Field, DataType::Keyword).with_format("xsv"))
+        .field(FieldDefinition::new("shorttitle", FieldType::Field, DataType::Literal).with_label(true))
+        .field(FieldDefinition::new("year", FieldType::Field, DataType::Datepart).with_nullok(true))
+        .entryfields(&[], &["title", "keywords", "year"])
+        .entryfields(&["article"], &["author", "shorttitle"])
+        .multiscript_field("title")
+        .constraints(&["article"], vec![
+            Constraint::mandatory(vec![ConstraintItem::Field("author".into()), ConstraintItem::Field("title".into())]),
+            Constraint::conditional(
+                Condition { quant: Quantifier::One, field: vec!["shorttitle".into()] },
+                Condition { quant: Quantifier::All, field: vec!["title".into()] },
+            ),
+        ])
+        .constraints(&[], vec![Constraint { rangemin: Some(0), ..Constraint::data("datepart", &["year"]) }]);
+    let datamodel = builder.build();
+
+    assert_eq!(datamodel.entrytypes().count(), 2);
+    assert!(datamodel.entrytype("note").unwrap().skip_output());
+    assert_eq!(datamodel.fields_for_entrytype("article"), ["title", "keywords", "year", "author", "shorttitle"]);
+    assert_eq!(datamodel.fields_for_entrytype("note"), ["title", "keywords", "year"]);
+    assert_eq!(datamodel.mandatory_fields("article"), [Mandatory::Field("author".into()), Mandatory::Field("title".into())]);
+    assert_eq!(datamodel.field("keywords").unwrap().format(), "xsv");
+    assert!(datamodel.is_multiscript("title"));
+    assert_eq!(datamodel.constraints("note").count(), 1);
+    assert_eq!(datamodel.nameparts(), ["family", "given"]);
+    assert_eq!(datamodel.constant("title"), Some(&ConstantValue::String("Notes".into())));
+    // biblatex's defaults for constants not given
+    assert_eq!(datamodel.genders().len(), 7);
+
+    let written = write_controlfile(&datamodel);
+    let reread: super::Controlfile = written.parse().unwrap();
+    assert_eq!(datamodel, reread.datamodel);
+
+    // extending the default
+    let mut builder = DatamodelBuilder::from(super::Datamodel::default());
+    builder
+        .entrytype("review", false)
+        .field(FieldDefinition::new("reviewed", FieldType::Field, DataType::Entrykey))
+        .entryfields(&["review"], &["reviewed"]);
+    let datamodel = builder.build();
+    assert!(datamodel.is_field_for_entrytype("review", "reviewed"));
+    assert!(datamodel.is_field_for_entrytype("review", "abstract"));
+    assert!(!datamodel.is_field_for_entrytype("article", "reviewed"));
+    assert!(datamodel.is_field_for_entrytype("article", "journaltitle"));
+}
+
+#[test]
+fn merge_datamodels() {
+    use super::{
+        Conflict, ConstantValue, Constraint, DataType, Datamodel, DatamodelBuilder, FieldDefinition, FieldType,
+        Mandatory,
+    };
+
+    let default = Datamodel::default();
+    let mut extension = DatamodelBuilder::new();
+    extension
+        .entrytype("report", false)
+        .entrytype("internalreport", false)
+        .field(FieldDefinition::new("title", FieldType::Field, DataType::Literal))
+        .field(FieldDefinition::new("department", FieldType::List, DataType::Literal))
+        .entryfields(&["internalreport"], &["department", "title"])
+        .multiscript_field("department")
+        .constraints(&["internalreport"], vec![Constraint::mandatory(vec![super::ConstraintItem::Field("department".into())])]);
+    let extension = extension.build();
+
+    let merged = default.merge(&extension).unwrap();
+    assert_eq!(merged.entrytypes().count(), default.entrytypes().count() + 1);
+    assert_eq!(merged.fields().count(), default.fields().count() + 1);
+    assert!(merged.is_field_for_entrytype("internalreport", "department"));
+    assert!(merged.is_field_for_entrytype("internalreport", "abstract"));
+    assert!(!merged.is_field_for_entrytype("report", "department"));
+    assert!(merged.is_multiscript("department") && merged.is_multiscript("title"));
+    assert_eq!(merged.mandatory_fields("internalreport"), [Mandatory::Field("department".into())]);
+    assert_eq!(merged.mandatory_fields("report"), default.mandatory_fields("report"));
+    assert_eq!(merged.merge(&extension).unwrap(), merged);
+    assert_eq!(default.merge(&default).unwrap(), default);
+
+    let mut conflicting = DatamodelBuilder::new();
+    conflicting.field(FieldDefinition::new("title", FieldType::List, DataType::Name));
+    let conflict = default.merge(&conflicting.build()).unwrap_err();
+    assert!(matches!(&conflict, Conflict::Field(own, other) if own.datatype() == DataType::Literal && other.datatype() == DataType::Name));
+    assert_eq!(
+        conflict.to_string(),
+        "Conflicting definitions of field 'title': fieldtype=field datatype=literal and fieldtype=list datatype=name",
+    );
+
+    let mut conflicting = DatamodelBuilder::new();
+    conflicting.entrytype("set", false).constant("nameparts", ConstantValue::List(vec!["family".into()]));
+    assert_eq!(
+        default.merge(&conflicting.build()).unwrap_err(),
+        Conflict::Constant(
+            "nameparts".into(),
+            ConstantValue::List(["family", "given", "prefix", "suffix"].map(String::from).to_vec()),
+            ConstantValue::List(vec!["family".into()]),
+        ),
+    );
+    let mut conflicting = DatamodelBuilder::new();
+    conflicting.entrytype("set", true);
+    assert_eq!(default.merge(&conflicting.build()).unwrap_err(), Conflict::Entrytype("set".into()));
+}
+
+#[test]
+fn json_schema() {
+    use serde_json::json;
+    use super::{Condition, Constraint, DataType, DatamodelBuilder, FieldDefinition, FieldType, Quantifier};
+
+    let schema = super::Datamodel::default().json_schema();
+    let defs = &schema["$defs"];
+    assert_eq!(schema["items"], json!({ "$ref": "#/$defs/entry" }));
+    assert_eq!(defs["entry"]["oneOf"].as_array().unwrap().len(), 51);
+    assert_eq!(defs["entry"]["oneOf"][0], json!({ "$ref": "#/$defs/entrytype-article" }));
+
+    let article = &defs["entrytype-article"]["properties"];
+    assert_eq!(article["entrytype"], json!({ "const": "article" }));
+    let fields = &article["fields"];
+    assert_eq!(fields["required"], json!(["author", "journaltitle", "title"]));
+    assert_eq!(fields["allOf"], json!([{ "oneOf": [{ "required": ["date"] }, { "required": ["year"] }] }]));
+    assert_eq!(fields["additionalProperties"], json!(false));
+    assert_eq!(fields["properties"]["author"], json!({ "type": "array", "items": { "$ref": "#/$defs/datatype-name" } }));
+    assert_eq!(fields["properties"]["keywords"], json!({ "$ref": "#/$defs/datatype-keyword" }));
+    assert_eq!(fields["properties"]["gender"], json!({ "allOf": [
+        { "$ref": "#/$defs/datatype-literal" },
+        { "pattern": "^(?:(?:sf|sm|sn|pf|pm|pn|pp))$" },
+    ] }));
+    assert!(fields["properties"]["date"].is_object());
+    assert!(fields["properties"].get("isbn").is_none());
+    assert!(defs["entrytype-booklet"]["properties"]["fields"]["properties"].get("eventdate").is_none());
+
+    let name = &defs["datatype-name"];
+    assert_eq!(name["properties"].as_object().unwrap().keys().collect::<Vec<_>>(), ["family", "given", "prefix", "suffix"]);
+    assert_eq!(name["required"], json!(["family"]));
+    assert_eq!(defs["datatype-integer"], json!({ "type": "integer" }));
+    // options like `maxnames=2` have integer values
+    let option = &defs["datatype-option"];
+    assert_eq!(option["type"], "object");
+    assert!(option["additionalProperties"]["type"].as_array().unwrap().contains(&json!("integer")));
+
+    let mut builder = DatamodelBuilder::new();
+    builder
+        .entrytype("report", false)
+        .field(FieldDefinition::new("number", FieldType::Field, DataType::Integer))
+        .field(FieldDefinition::new("code", FieldType::Field, DataType::Literal))
+        .field(FieldDefinition::new("institution", FieldType::List, DataType::Literal))
+        .entryfields(&["report"], &["number", "code", "institution"])
+        .constraints(&["report"], vec![
+            Constraint { rangemin: Some(1), ..Constraint::data("integer", &["number"]) },
+            Constraint { pattern: Some("[A-Z]+".into()), ..Constraint::data("pattern", &["code"]) },
+            Constraint::conditional(
+                Condition { quant: Quantifier::One, field: vec!["number".into()] },
+                Condition { quant: Quantifier::None, field: vec!["code".into()] },
+            ),
+        ]);
+    let schema = builder.build().json_schema();
+    let fields = &schema["$defs"]["entrytype-report"]["properties"]["fields"];
+    assert_eq!(fields["properties"]["number"]["allOf"][1], json!({ "type": "integer", "minimum": 1 }));
+    assert_eq!(fields["properties"]["code"]["allOf"][1], json!({ "pattern": "^(?:[A-Z]+)$" }));
+    assert_eq!(fields["properties"]["institution"]["type"], "array");
+    assert_eq!(fields["allOf"], json!([{
+        "if": { "anyOf": [{ "required": ["number"] }] },
+        "then": { "not": { "anyOf": [{ "required": ["code"] }] } },
+    }]));
+}
+
+#[test]
+fn write_controlfile_round_trip() {
+    let original = std::fs::read_to_string(test_file("full-controlfile.bcf")).unwrap();
+    let controlfile: super::Controlfile = original.parse().unwrap();
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b' ', 2);
+    controlfile.write(&mut writer).unwrap();
+    let written = String::from_utf8(writer.into_inner()).unwrap();
+
+    assert!(written.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    assert!(written.contains(r#"<bcf:controlfile version="3.7" bltxversion="3.16" xmlns:bcf="https://sourceforge.net/projects/biblatex">"#));
+    assert_eq!(elements(&original, "bcf:controlfile"), elements(&written, "bcf:controlfile"));
+    let reread: super::Controlfile = written.parse().unwrap();
+    assert_eq!(controlfile, reread);
+    assert_eq!(controlfile.presorts, [super::Presort { r#type: None, presort: "mm".into() }]);
+}
+
+#[test]
+fn write_unknown_elements() {
+    let unknown = r#"<bcf:future order="1"><bcf:item>a &amp; b</bcf:item><bcf:flag/></bcf:future>
+  <bcf:later/>
+</bcf:controlfile>"#;
+    let original = std::fs::read_to_string(test_file("full-controlfile.bcf")).unwrap()
+        .replace("</bcf:controlfile>", unknown);
+    let controlfile: super::Controlfile = original.parse().unwrap();
+    let names: Vec<&str> = controlfile.unknown.iter().map(|element| element.name.as_str()).collect();
+    assert_eq!(names, ["bcf:future", "bcf:later"]);
+
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b' ', 2);
+    controlfile.write(&mut writer).unwrap();
+    let written = String::from_utf8(writer.into_inner()).unwrap();
+    assert!(written.contains("<bcf:item>a &amp; b</bcf:item>"));
+    assert_eq!(elements(&original, "bcf:controlfile"), elements(&written, "bcf:controlfile"));
+    assert_eq!(controlfile, written.parse().unwrap());
+}
+
+#[test]
+fn write_interleaves_sections() {
+    let mut controlfile = super::Controlfile::from_file(test_file("full-controlfile.bcf")).unwrap();
+    controlfile.bibdata.reverse();
+    controlfile.sections.push(super::Section { number: 2, citekey: Vec::new() });
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    controlfile.write(&mut writer).unwrap();
+    let written = String::from_utf8(writer.into_inner()).unwrap();
+    let position = |tag: &str| written.find(tag).unwrap();
+    assert!(position("<bcf:bibdata section=\"0\"") < position("<bcf:section number=\"0\""));
+    assert!(position("<bcf:section number=\"0\"") < position("<bcf:bibdata section=\"1\""));
+    assert!(position("<bcf:bibdata section=\"1\"") < position("<bcf:section number=\"1\""));
+    assert!(position("<bcf:section number=\"1\"") < position("<bcf:section number=\"2\""));
+    assert!(position("<bcf:datamodel>") < position("<bcf:bibdata section=\"0\""));
+    assert!(position("<bcf:section number=\"2\"") < position("<bcf:sortingtemplate"));
+}
@@ -0,0 +1,63 @@
+use std::io::Write;
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Result, Writer};
+
+
+/// Attributes of an element in order, those without a value are left out.
+pub(crate) type Attributes<'a> = [(&'a str, Option<String>)];
+
+pub(crate) fn start<W: Write>(writer: &mut Writer<W>, name: &str, attributes: &Attributes) -> Result<()> {
+    writer.write_event(Event::Start(tag(name, attributes)))
+}
+
+pub(crate) fn end<W: Write>(writer: &mut Writer<W>, name: &str) -> Result<()> {
+    writer.write_event(Event::End(BytesEnd::borrowed(name.as_bytes())))
+}
+
+pub(crate) fn empty<W: Write>(writer: &mut Writer<W>, name: &str, attributes: &Attributes) -> Result<()> {
+    writer.write_event(Event::Empty(tag(name, attributes)))
+}
+
+/// An element with only text content.
+pub(crate) fn text<W: Write>(writer: &mut Writer<W>, name: &str, attributes: &Attributes, text: &str) -> Result<()> {
+    writer.write_event(Event::Start(tag(name, attributes)))?;
+    writer.write_event(Event::Text(BytesText::from_plain_str(text)))?;
+    end(writer, name)
+}
+
+/// Text elements `name` for each of `texts`.
+pub(crate) fn texts<W: Write, S: AsRef<str>>(writer: &mut Writer<W>, name: &str, texts: &[S]) -> Result<()> {
+    for value in texts {
+        text(writer, name, &[], value.as_ref())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn attr<T: ToString>(value: T) -> Option<String> {
+    Some(value.to_string())
+}
+
+pub(crate) fn opt<T: ToString>(value: &Option<T>) -> Option<String> {
+    value.as_ref().map(ToString::to_string)
+}
+
+/// `true` for set flags, which are left out otherwise.
+pub(crate) fn flag(value: bool) -> Option<String> {
+    value.then(|| "true".to_owned())
+}
+
+/// `1` or `0`, as biblatex writes most booleans.
+pub(crate) fn boolean(value: Option<bool>) -> Option<String> {
+    value.map(|value| if value { "1" } else { "0" }.to_owned())
+}
+
+fn tag<'a>(name: &'a str, attributes: &'a Attributes) -> BytesStart<'a> {
+    let mut tag = BytesStart::borrowed_name(name.as_bytes());
+    for (key, value) in attributes {
+        if let Some(value) = value {
+            tag.push_attribute((*key, value.as_str()));
+        }
+    }
+    tag
+}
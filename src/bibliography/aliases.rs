@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+
+/// Mapping of legacy BibTeX entry types and fields to their biblatex names.
+/// The default is the alias table of biber's BibTeX driver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aliases {
+    entrytypes: HashMap<String, EntrytypeAlias>,
+    fields: HashMap<String, String>,
+}
+
+/// Target of an entry type alias. `subtype` is set as the `type` field
+/// unless the entry already has one, e.g. `@phdthesis` is `@thesis` with
+/// `type = {phdthesis}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntrytypeAlias {
+    pub entrytype: String,
+    pub subtype: Option<String>,
+}
+
+const ENTRYTYPES: &[(&str, &str, Option<&str>)] = &[
+    ("conference", "inproceedings", None),
+    ("electronic", "online", None),
+    ("mastersthesis", "thesis", Some("mathesis")),
+    ("phdthesis", "thesis", Some("phdthesis")),
+    ("techreport", "report", Some("techreport")),
+    ("www", "online", None),
+];
+
+const FIELDS: &[(&str, &str)] = &[
+    ("address", "location"),
+    ("annote", "annotation"),
+    ("archiveprefix", "eprinttype"),
+    ("hyphenation", "langid"),
+    ("journal", "journaltitle"),
+    ("key", "sortkey"),
+    ("pdf", "file"),
+    ("primaryclass", "eprintclass"),
+    ("school", "institution"),
+];
+
+
+impl Default for Aliases {
+    fn default() -> Self {
+        let mut aliases = Aliases::new();
+        for (alias, entrytype, subtype) in ENTRYTYPES {
+            aliases.alias_entrytype(*alias, *entrytype, subtype.map(str::to_owned));
+        }
+        for (alias, field) in FIELDS {
+            aliases.alias_field(*alias, *field);
+        }
+        aliases
+    }
+}
+
+impl Aliases {
+
+    /// An empty table, which leaves all names as they are.
+    pub fn new() -> Self {
+        Aliases { entrytypes: HashMap::new(), fields: HashMap::new() }
+    }
+
+    /// Adds or replaces an entry type alias. Names are lowercase.
+    pub fn alias_entrytype<A: Into<String>, T: Into<String>>(&mut self, alias: A, entrytype: T, subtype: Option<String>) {
+        self.entrytypes.insert(alias.into(), EntrytypeAlias { entrytype: entrytype.into(), subtype });
+    }
+
+    /// Adds or replaces a field alias. Names are lowercase.
+    pub fn alias_field<A: Into<String>, F: Into<String>>(&mut self, alias: A, field: F) {
+        self.fields.insert(alias.into(), field.into());
+    }
+
+    pub fn remove_entrytype(&mut self, alias: &str) -> Option<EntrytypeAlias> {
+        self.entrytypes.remove(alias)
+    }
+
+    pub fn remove_field(&mut self, alias: &str) -> Option<String> {
+        self.fields.remove(alias)
+    }
+
+    pub fn entrytype(&self, alias: &str) -> Option<&EntrytypeAlias> {
+        self.entrytypes.get(alias)
+    }
+
+    pub fn field(&self, alias: &str) -> Option<&str> {
+        self.fields.get(alias).map(String::as_str)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use super::Entry;
+use crate::bcf;
+
+
+/// Rules for copying fields from a `crossref` parent into its children, as
+/// declared with `\DefaultInheritance` and `\DeclareDataInheritance`. The
+/// default are the rules of `biblatex.def`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inheritance {
+    pub defaults: InheritanceDefaults,
+    pub rules: Vec<InheritanceRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InheritanceDefaults {
+    /// Copy all fields not handled by a rule.
+    pub inherit_all: bool,
+    /// Replace fields the child already has.
+    pub override_target: bool,
+    /// Exceptions for some parent and child entry types.
+    pub type_pairs: Vec<DefaultTypePair>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultTypePair {
+    pub source: String,
+    pub target: String,
+    pub inherit_all: bool,
+    pub override_target: bool,
+}
+
+/// Field rules for pairs of parent and child entry type, `*` matches any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InheritanceRule {
+    pub type_pairs: Vec<(String, String)>,
+    pub fields: Vec<FieldInheritance>,
+}
+
+/// `\inherit{source}{target}`, or `\noinherit{source}` if `target` is `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldInheritance {
+    pub source: String,
+    pub target: Option<String>,
+    /// Overrides [`InheritanceDefaults::override_target`] for this field.
+    pub override_target: Option<bool>,
+}
+
+
+const NO_INHERIT: &[&str] = &[
+    "ids", "crossref", "xref", "entryset", "entrysubtype", "execute", "label", "options", "presort",
+    "related", "relatedoptions", "relatedstring", "relatedtype", "shorthand", "shorthandintro", "sortkey",
+];
+
+const TITLES: &[&str] = &["title", "subtitle", "titleaddon"];
+const NO_INHERIT_TITLES: &[&str] = &["shorttitle", "sorttitle", "indextitle", "indexsorttitle"];
+
+impl Default for Inheritance {
+    fn default() -> Self {
+        let titles = |prefix: &str| {
+            let mut fields: Vec<_> = TITLES.iter()
+                .map(|title| FieldInheritance::new(*title, Some(format!("{}{}", prefix, title))))
+                .collect();
+            fields.extend(NO_INHERIT_TITLES.iter().map(|title| FieldInheritance::new(*title, None)));
+            fields
+        };
+        let rules = vec![
+            InheritanceRule::new(&["*"], &["*"], NO_INHERIT.iter().map(|field| FieldInheritance::new(*field, None)).collect()),
+            InheritanceRule::new(&["mvbook", "book"], &["inbook", "bookinbook", "suppbook"], vec![
+                FieldInheritance::new("author", Some("author".to_owned())),
+                FieldInheritance::new("author", Some("bookauthor".to_owned())),
+            ]),
+            InheritanceRule::new(&["mvbook"], &["book", "inbook", "bookinbook", "suppbook"], titles("main")),
+            InheritanceRule::new(
+                &["mvcollection", "mvreference"],
+                &["collection", "reference", "incollection", "inreference", "suppcollection"],
+                titles("main"),
+            ),
+            InheritanceRule::new(&["mvproceedings"], &["proceedings", "inproceedings"], titles("main")),
+            InheritanceRule::new(&["book"], &["inbook", "bookinbook", "suppbook"], titles("book")),
+            InheritanceRule::new(&["collection", "reference"], &["incollection", "inreference", "suppcollection"], titles("book")),
+            InheritanceRule::new(&["proceedings"], &["inproceedings"], titles("book")),
+            InheritanceRule::new(&["periodical"], &["article", "suppperiodical"], titles("journal")),
+        ];
+        Inheritance {
+            defaults: InheritanceDefaults { inherit_all: true, override_target: false, type_pairs: Vec::new() },
+            rules,
+        }
+    }
+}
+
+/// Rules as written to the control file. Without `bcf:defaults`, biblatex's
+/// defaults apply.
+impl From<&bcf::Inheritance> for Inheritance {
+    fn from(inheritance: &bcf::Inheritance) -> Self {
+        let defaults = match &inheritance.defaults {
+            Some(defaults) => InheritanceDefaults {
+                inherit_all: defaults.inherit_all,
+                override_target: defaults.override_target,
+                type_pairs: defaults.type_pair.iter()
+                    .map(|pair| DefaultTypePair {
+                        source: pair.source.clone(),
+                        target: pair.target.clone(),
+                        inherit_all: pair.inherit_all.unwrap_or(defaults.inherit_all),
+                        override_target: pair.override_target.unwrap_or(defaults.override_target),
+                    })
+                    .collect(),
+            },
+            None => Inheritance::default().defaults,
+        };
+        let rules = inheritance.inherit.iter()
+            .map(|inherit| InheritanceRule {
+                type_pairs: inherit.type_pair.iter().map(|pair| (pair.source.clone(), pair.target.clone())).collect(),
+                fields: inherit.field.iter()
+                    .map(|field| FieldInheritance {
+                        source: field.source.clone(),
+                        target: if field.skip == Some(true) { None } else { field.target.clone() },
+                        override_target: field.override_target,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Inheritance { defaults, rules }
+    }
+}
+
+impl InheritanceRule {
+
+    /// Rule for all combinations of `sources` and `targets`.
+    pub fn new(sources: &[&str], targets: &[&str], fields: Vec<FieldInheritance>) -> Self {
+        let type_pairs = sources.iter()
+            .flat_map(|source| targets.iter().map(move |target| (source.to_string(), target.to_string())))
+            .collect();
+        InheritanceRule { type_pairs, fields }
+    }
+
+    fn matches(&self, source: &str, target: &str) -> bool {
+        self.type_pairs.iter().any(|(s, t)| type_matches(s, source) && type_matches(t, target))
+    }
+}
+
+impl FieldInheritance {
+
+    pub fn new<S: Into<String>>(source: S, target: Option<String>) -> Self {
+        FieldInheritance { source: source.into(), target, override_target: None }
+    }
+}
+
+impl Inheritance {
+
+    /// Copies the fields of `parent` into `child`.
+    pub(super) fn apply<'de>(&self, parent: &Entry<'de>, child: &mut Entry<'de>) {
+        let (inherit_all, override_target) = self.defaults.type_pairs.iter()
+            .find(|pair| type_matches(&pair.source, &parent.entrytype) && type_matches(&pair.target, &child.entrytype))
+            .map_or((self.defaults.inherit_all, self.defaults.override_target), |pair| (pair.inherit_all, pair.override_target));
+
+        let mut handled = HashSet::new();
+        let rules = self.rules.iter().filter(|rule| rule.matches(&parent.entrytype, &child.entrytype));
+        for field in rules.flat_map(|rule| &rule.fields) {
+            handled.insert(field.source.as_str());
+            let (Some(target), Some(value)) = (&field.target, parent.fields.get(&field.source)) else { continue };
+            if field.override_target.unwrap_or(override_target) || !child.fields.contains_key(target) {
+                child.fields.insert(target.clone(), value.clone());
+            }
+        }
+
+        if inherit_all {
+            for (name, value) in &parent.fields {
+                if !handled.contains(name.as_str()) && (override_target || !child.fields.contains_key(name)) {
+                    child.fields.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+
+fn type_matches(pattern: &str, entrytype: &str) -> bool {
+    pattern == "*" || pattern == entrytype
+}
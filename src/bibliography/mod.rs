@@ -0,0 +1,499 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::bcf::{DataType, Datamodel, FieldType};
+use crate::bibtex::{Error, InputSlice, InputTrace, RawBibliography, RawEntry};
+use crate::latex::Text;
+use crate::value::{List, OptionValue, Value};
+
+
+mod aliases;
+mod inheritance;
+mod related;
+mod validate;
+mod xdata;
+
+pub use aliases::{Aliases, EntrytypeAlias};
+pub use inheritance::{DefaultTypePair, FieldInheritance, Inheritance, InheritanceDefaults, InheritanceRule};
+pub use validate::Warning;
+
+
+/// Entries with macros expanded and fields typed according to a [`Datamodel`].
+#[derive(Debug, Default)]
+pub struct Bibliography<'de> {
+    entries: HashMap<String, Entry<'de>>,
+    /// Keys from `ids` fields and the key of their entry.
+    ids: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry<'de> {
+    entrytype: String,
+    key: String,
+    source: InputSlice<'de>,
+    fields: HashMap<String, Field<'de>>,
+    inset: Option<String>,
+    clone_source: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field<'de> {
+    name: InputSlice<'de>,
+    raw: Text<'de>,
+    value: Value,
+}
+
+/// An entry with macros expanded and names aliased, before typing.
+#[derive(Debug, Clone)]
+struct Draft<'de> {
+    entrytype: String,
+    /// `type` implied by an entry type alias.
+    subtype: Option<String>,
+    entrytype_source: InputSlice<'de>,
+    key_source: InputSlice<'de>,
+    fields: HashMap<String, DraftField<'de>>,
+}
+
+#[derive(Debug, Clone)]
+struct DraftField<'de> {
+    name: InputSlice<'de>,
+    raw: Text<'de>,
+    /// LaTeX to be typed, which differs from `raw` after resolving granular
+    /// XDATA references.
+    content: String,
+}
+
+
+// Bibliography
+// ------------
+
+impl<'de> Bibliography<'de> {
+
+    /// Expands macros and types every field according to `datamodel`. Fields
+    /// whose content does not fit their datatype are kept as literals of their
+    /// content and reported.
+    /// Fields unknown to the datamodel are kept as literals. Legacy BibTeX
+    /// names are mapped with the default [`Aliases`].
+    pub fn resolve(raw: &RawBibliography<'de>, datamodel: &Datamodel) -> (Self, Vec<Error>) {
+        Self::resolve_with_aliases(raw, datamodel, &Aliases::default())
+    }
+
+    /// Like [`Bibliography::resolve`], with a custom alias table. An aliased
+    /// field is dropped and reported if the entry also has its target field.
+    ///
+    /// XDATA is resolved before typing: entries get the fields of the
+    /// `@xdata` entries in their `xdata` field unless they have them already,
+    /// and granular references like `xdata=key-field` or `xdata=key-field-2`
+    /// (an item of a list field) are replaced. `@xdata` entries are removed.
+    pub fn resolve_with_aliases(raw: &RawBibliography<'de>, datamodel: &Datamodel, aliases: &Aliases) -> (Self, Vec<Error>) {
+        let mut errors = Vec::new();
+        let mut drafts: HashMap<String, Draft<'de>> = raw.entries.values()
+            .map(|raw_entry| (raw_entry.key.as_str().to_owned(), Draft::new(raw, raw_entry, aliases, &mut errors)))
+            .collect();
+        xdata::resolve(&mut drafts, datamodel, &mut errors);
+
+        let mut bib = Bibliography::default();
+        for (key, draft) in drafts {
+            if draft.entrytype != "xdata" {
+                bib.entries.insert(key.clone(), Entry::resolve(key, draft, datamodel, &mut errors));
+            }
+        }
+        (bib, errors)
+    }
+
+    /// Copies inherited fields from `crossref` parents into their children,
+    /// parents with a `crossref` of their own first. Returns the keys of
+    /// parents referenced by at least `mincrossrefs` children, which biber
+    /// adds to the bibliography even if they are not cited (`0` disables this).
+    pub fn resolve_crossrefs(&mut self, inheritance: &Inheritance, mincrossrefs: usize) -> (Vec<String>, Vec<Error>) {
+        let mut errors = Vec::new();
+        let mut done = HashSet::new();
+        let keys: Vec<String> = self.entries.keys().cloned().collect();
+        for key in &keys {
+            self.inherit_crossref(key, inheritance, &mut done, &mut Vec::new(), &mut errors);
+        }
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for entry in self.entries.values() {
+            if let Some((parent, _)) = entry.key_field("crossref") {
+                if self.entries.contains_key(parent) {
+                    *counts.entry(parent).or_default() += 1;
+                }
+            }
+        }
+        let mut included: Vec<String> = counts.into_iter()
+            .filter(|(_, count)| mincrossrefs > 0 && *count >= mincrossrefs)
+            .map(|(parent, _)| parent.to_owned())
+            .collect();
+        included.sort();
+        (included, errors)
+    }
+
+    fn inherit_crossref(&mut self, key: &str, inheritance: &Inheritance, done: &mut HashSet<String>, stack: &mut Vec<String>, errors: &mut Vec<Error>) {
+        if done.contains(key) {
+            return;
+        }
+        let Some((parent, trace)) = self.entries.get(key).and_then(|entry| entry.key_field("crossref"))
+            .map(|(parent, field)| (parent.to_owned(), field.trace())) else {
+            done.insert(key.to_owned());
+            return;
+        };
+        if stack.iter().any(|other| other == key) {
+            errors.push(Error::CircularReference(key.to_owned(), trace));
+            done.insert(key.to_owned());
+            return;
+        }
+        if !self.entries.contains_key(&parent) {
+            errors.push(Error::UndefinedEntry(parent, trace));
+            done.insert(key.to_owned());
+            return;
+        }
+        stack.push(key.to_owned());
+        self.inherit_crossref(&parent, inheritance, done, stack, errors);
+        stack.pop();
+        // a cycle was reported further down
+        if done.contains(key) {
+            return;
+        }
+
+        let parent = self.entries[&parent].clone();
+        if let Some(child) = self.entries.get_mut(key) {
+            inheritance.apply(&parent, child);
+        }
+        done.insert(key.to_owned());
+    }
+
+    /// Adds a `@set` entry, as defined with `\defbibentryset` in the
+    /// control file. Fails if the key is taken.
+    pub fn add_dynamic_set(&mut self, key: &str, members: Vec<String>) -> Result<(), Error> {
+        let source = InputSlice::generated("entryset");
+        if let Some(other) = self.entries.get(key) {
+            return Err(Error::DoubleKey(key.to_owned(), other.trace(), source.trace()));
+        }
+        let field = Field { name: source.clone(), raw: Text::default(), value: Value::Keys(List::from(members)) };
+        let entry = Entry {
+            entrytype: "set".to_owned(),
+            key: key.to_owned(),
+            source,
+            fields: HashMap::from([("entryset".to_owned(), field)]),
+            inset: None,
+            clone_source: None,
+        };
+        self.entries.insert(key.to_owned(), entry);
+        Ok(())
+    }
+
+    /// Links `@set` entries and their members: members missing from the data
+    /// are reported and removed from the `entryset`, the others get the set
+    /// as [`Entry::inset`].
+    pub fn resolve_sets(&mut self) -> Vec<Error> {
+        let mut errors = Vec::new();
+        let mut sets: Vec<String> = self.entries.values()
+            .filter(|entry| entry.entrytype == "set")
+            .map(|entry| entry.key.clone())
+            .collect();
+        sets.sort();
+
+        for key in sets {
+            let Some(field) = self.entries[&key].get("entryset") else { continue };
+            let Value::Keys(members) = field.value() else { continue };
+            let mut found = Vec::new();
+            for member in members.items() {
+                if self.entries.contains_key(member) {
+                    found.push(member.clone());
+                } else {
+                    errors.push(Error::UndefinedEntry(member.clone(), field.trace()));
+                }
+            }
+            for member in &found {
+                if let Some(entry) = self.entries.get_mut(member) {
+                    entry.inset = Some(key.clone());
+                }
+            }
+            if let Some(field) = self.entries.get_mut(&key).and_then(|set| set.get_mut("entryset")) {
+                field.set_value(Value::Keys(List::from(found)));
+            }
+        }
+        errors
+    }
+
+    /// Registers the keys in `ids` fields as aliases of their entry. Aliases
+    /// taken by an entry or an earlier alias are reported with both traces.
+    pub fn resolve_ids(&mut self) -> Vec<Error> {
+        let mut errors = Vec::new();
+        let mut keys: Vec<&String> = self.entries.keys().collect();
+        keys.sort();
+        for key in keys {
+            let Some(field) = self.entries[key].get("ids") else { continue };
+            let Value::Keys(ids) = field.value() else { continue };
+            for id in ids.items() {
+                if let Some(other) = self.entries.get(id) {
+                    if other.key != *key {
+                        errors.push(Error::DoubleKey(id.clone(), other.trace(), field.trace()));
+                    }
+                } else if let Some(other) = self.ids.get(id) {
+                    let other = &self.entries[other].fields["ids"];
+                    errors.push(Error::DoubleKey(id.clone(), other.trace(), field.trace()));
+                } else {
+                    self.ids.insert(id.clone(), key.clone());
+                }
+            }
+        }
+        errors
+    }
+
+    /// Key of the entry cited as `key`, which may be an alias from an `ids`
+    /// field, see [`Bibliography::resolve_ids`].
+    pub fn canonical_key<'a>(&'a self, key: &'a str) -> Option<&'a str> {
+        if self.entries.contains_key(key) {
+            Some(key)
+        } else {
+            self.ids.get(key).map(String::as_str)
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry<'de>> {
+        self.entries.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Entry<'de>> {
+        self.entries.get_mut(key)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &Entry<'de>> {
+        self.entries.values()
+    }
+
+    pub fn insert(&mut self, entry: Entry<'de>) -> Option<Entry<'de>> {
+        self.entries.insert(entry.key.clone(), entry)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Entry<'de>> {
+        self.ids.retain(|_, other| other != key);
+        self.entries.remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+
+// Entry
+// -----
+
+impl<'de> Entry<'de> {
+
+    fn resolve(key: String, draft: Draft<'de>, datamodel: &Datamodel, errors: &mut Vec<Error>) -> Self {
+        let mut entry = Entry {
+            entrytype: draft.entrytype,
+            key,
+            source: draft.key_source,
+            fields: HashMap::new(),
+            inset: None,
+            clone_source: None,
+        };
+        for (name, field) in draft.fields {
+            let field = Field::resolve(&name, field, datamodel, errors);
+            entry.fields.insert(name, field);
+        }
+        if let Some(subtype) = draft.subtype {
+            entry.fields.entry("type".to_owned()).or_insert_with(|| Field {
+                name: draft.entrytype_source.clone(),
+                raw: Text::parse(&draft.entrytype_source),
+                value: Value::Key(subtype),
+            });
+        }
+        entry
+    }
+
+    /// Entry type in lowercase.
+    pub fn entrytype(&self) -> &str {
+        &self.entrytype
+    }
+
+    pub fn set_entrytype<T: Into<String>>(&mut self, entrytype: T) {
+        self.entrytype = entrytype.into();
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Location of the entry key in the input.
+    pub fn trace(&self) -> InputTrace {
+        self.source.trace()
+    }
+
+    /// Field by its lowercase name.
+    pub fn get(&self, name: &str) -> Option<&Field<'de>> {
+        self.fields.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Field<'de>> {
+        self.fields.get_mut(name)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &Field<'de>)> {
+        self.fields.iter().map(|(name, field)| (name.as_str(), field))
+    }
+
+    pub fn insert<T: Into<String>>(&mut self, name: T, field: Field<'de>) -> Option<Field<'de>> {
+        self.fields.insert(name.into(), field)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Field<'de>> {
+        self.fields.remove(name)
+    }
+
+    /// Entry key referenced by a field like `crossref`.
+    fn key_field(&self, name: &str) -> Option<(&str, &Field<'de>)> {
+        let field = self.get(name)?;
+        match field.value() {
+            Value::Key(key) => Some((key.as_str(), field)),
+            _ => None,
+        }
+    }
+
+    /// Members of a `@set` entry, in order.
+    pub fn entryset(&self) -> Option<&[String]> {
+        match self.get("entryset").map(Field::value) {
+            Some(Value::Keys(members)) => Some(members.items()),
+            _ => None,
+        }
+    }
+
+    /// Keys of related entries, or of their clones after
+    /// [`Bibliography::resolve_related`].
+    pub fn related(&self) -> Option<&[String]> {
+        match self.get("related").map(Field::value) {
+            Some(Value::Keys(related)) => Some(related.items()),
+            _ => None,
+        }
+    }
+
+    /// Key of the set this entry is a member of, see [`Bibliography::resolve_sets`].
+    pub fn inset(&self) -> Option<&str> {
+        self.inset.as_deref()
+    }
+
+    /// Key of the entry this is a clone of, see [`Bibliography::resolve_related`].
+    pub fn clone_source(&self) -> Option<&str> {
+        self.clone_source.as_deref()
+    }
+
+    /// Whether the `keywords` field contains `keyword`, as used by the
+    /// `keyword` filter of `\printbibliography`.
+    pub fn has_keyword(&self, keyword: &str) -> bool {
+        match self.get("keywords").map(Field::value) {
+            Some(Value::Keywords(keywords)) => keywords.iter().any(|other| other == keyword),
+            _ => false,
+        }
+    }
+
+    /// Value of a per-entry option from the `options` field.
+    pub fn option(&self, name: &str) -> Option<&OptionValue> {
+        match self.get("options").map(Field::value) {
+            Some(Value::Options(options)) => options.iter().rev().find(|option| option.name == name).map(|option| &option.value),
+            _ => None,
+        }
+    }
+}
+
+
+// Draft
+// -----
+
+impl<'de> Draft<'de> {
+
+    fn new(raw: &RawBibliography<'de>, raw_entry: &RawEntry<'de>, aliases: &Aliases, errors: &mut Vec<Error>) -> Self {
+        let entrytype = raw_entry.entrytype.as_str().to_lowercase();
+        let alias = aliases.entrytype(&entrytype);
+        let mut draft = Draft {
+            subtype: alias.and_then(|alias| alias.subtype.clone()),
+            entrytype: alias.map_or(entrytype, |alias| alias.entrytype.clone()),
+            entrytype_source: raw_entry.entrytype.clone(),
+            key_source: raw_entry.key.clone(),
+            fields: HashMap::new(),
+        };
+
+        // aliased fields last, so that fields given under their proper name win
+        let mut defs: Vec<_> = raw_entry.fields.values().map(|def| {
+            let name = def.name.as_str().to_lowercase();
+            match aliases.field(&name) {
+                Some(target) => (true, target.to_owned(), def),
+                None => (false, name, def),
+            }
+        }).collect();
+        defs.sort_by_key(|(aliased, _, _)| *aliased);
+
+        for (_, name, def) in defs {
+            if let Some(other) = draft.fields.get(&name) {
+                errors.push(Error::DoubleField(name, other.name.trace(), def.name.trace()));
+                continue;
+            }
+            match raw.resolve_value(&def.value) {
+                Ok(text) => {
+                    let content = text.to_string();
+                    draft.fields.insert(name, DraftField { name: def.name.clone(), raw: text, content });
+                },
+                Err(error) => errors.push(error),
+            }
+        }
+        draft
+    }
+}
+
+
+// Field
+// -----
+
+impl<'de> Field<'de> {
+
+    /// Content that does not fit the datatype is kept as a literal, like biber
+    /// does, and reported.
+    fn resolve(name: &str, draft: DraftField<'de>, datamodel: &Datamodel, errors: &mut Vec<Error>) -> Self {
+        let value = match datamodel.field(name) {
+            Some(field) => Value::parse_with_nameparts(
+                &draft.content, field.fieldtype, field.datatype, field.format == "xsv", datamodel.nameparts(),
+            ),
+            None => Value::parse(&draft.content, FieldType::Field, DataType::Literal, false),
+        };
+        let value = value.unwrap_or_else(|message| {
+            errors.push(Error::InvalidValue(name.to_owned(), message, draft.name.trace()));
+            Value::Literal(draft.content.clone())
+        });
+        Field { name: draft.name, raw: draft.raw, value }
+    }
+
+    /// Field name as written in the input, before aliasing. For a `type`
+    /// implied by an entry type alias, this is the original entry type.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Location of the field name in the input.
+    pub fn trace(&self) -> InputTrace {
+        self.name.trace()
+    }
+
+    /// The LaTeX source of the value, with macros expanded.
+    pub fn raw(&self) -> &Text<'de> {
+        &self.raw
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: Value) {
+        self.value = value;
+    }
+}
+
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use crate::bibtex::{Error, InputSlice};
+use crate::latex::Text;
+use crate::value::{EntryOption, List, OptionValue, Value};
+use super::{Bibliography, Field};
+
+
+impl Bibliography<'_> {
+
+    /// Creates a data-only clone of every entry named in a `related` field
+    /// and points the `related` field to the clones, as biber does for
+    /// styles to print related entries. The clones get the options of the
+    /// `relatedoptions` field, `dataonly` by default. A clone is shared by all
+    /// entries relating to the same key, with the options of the first one.
+    /// `relatedtype` and `relatedstring` are kept on the relating entry.
+    /// Clones have no `related` field. A relation leading back to the entry,
+    /// directly or through other entries, is reported as circular and not
+    /// cloned.
+    pub fn resolve_related(&mut self) -> Vec<Error> {
+        let mut errors = Vec::new();
+        let mut keys: Vec<String> = self.entries.values()
+            .filter(|entry| entry.related().is_some())
+            .map(|entry| entry.key.clone())
+            .collect();
+        keys.sort();
+        let mut circular = HashSet::new();
+        let mut done = HashSet::new();
+        for key in &keys {
+            self.find_cycles(key, &mut Vec::new(), &mut done, &mut circular, &mut errors);
+        }
+        for key in &keys {
+            self.relate(key, &circular, &mut errors);
+        }
+        errors
+    }
+
+    /// Follows the relations of `key` in key order, collecting those back to
+    /// an entry on `stack` as `(key, target)`.
+    fn find_cycles(
+        &self,
+        key: &str,
+        stack: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        circular: &mut HashSet<(String, String)>,
+        errors: &mut Vec<Error>,
+    ) {
+        if done.contains(key) {
+            return;
+        }
+        let Some(entry) = self.entries.get(key) else { return };
+        stack.push(key.to_owned());
+        if let (Some(field), Some(related)) = (entry.get("related"), entry.related()) {
+            for target in related {
+                if stack.contains(target) {
+                    errors.push(Error::CircularReference(target.clone(), field.trace()));
+                    circular.insert((key.to_owned(), target.clone()));
+                } else {
+                    self.find_cycles(target, stack, done, circular, errors);
+                }
+            }
+        }
+        stack.pop();
+        done.insert(key.to_owned());
+    }
+
+    fn relate(&mut self, key: &str, circular: &HashSet<(String, String)>, errors: &mut Vec<Error>) {
+        let entry = &self.entries[key];
+        let Some(field) = entry.get("related") else { return };
+        let Value::Keys(related) = field.value() else { return };
+        let related = related.items().to_vec();
+        let trace_source = field.name.clone();
+        let options = match entry.get("relatedoptions").map(Field::value) {
+            Some(Value::Options(options)) => options.clone(),
+            _ => vec![EntryOption { name: "dataonly".to_owned(), value: OptionValue::Boolean(true) }],
+        };
+
+        let mut clones = Vec::new();
+        for target in related {
+            if circular.contains(&(key.to_owned(), target.clone())) {
+                continue;
+            }
+            let Some(original) = self.entries.get(&target) else {
+                errors.push(Error::UndefinedEntry(target, trace_source.trace()));
+                continue;
+            };
+
+            let clone_key = clone_key(&target);
+            match self.entries.get(&clone_key) {
+                Some(existing) if existing.clone_source.as_deref() == Some(target.as_str()) => (),
+                Some(existing) => {
+                    errors.push(Error::DoubleKey(clone_key, existing.trace(), trace_source.trace()));
+                    continue;
+                },
+                None => {
+                    let mut clone = original.clone();
+                    clone.key = clone_key.clone();
+                    clone.inset = None;
+                    clone.clone_source = Some(target);
+                    clone.fields.remove("related");
+                    clone.fields.insert("options".to_owned(), Field {
+                        name: InputSlice::generated("options"),
+                        raw: Text::default(),
+                        value: Value::Options(options.clone()),
+                    });
+                    self.entries.insert(clone_key.clone(), clone);
+                },
+            }
+            clones.push(clone_key);
+        }
+
+        if let Some(field) = self.entries.get_mut(key).and_then(|entry| entry.get_mut("related")) {
+            field.set_value(Value::Keys(List::from(clones)));
+        }
+    }
+}
+
+
+/// Stable key for the clone of `key`, a FNV-1a hash like biber's MD5 keys.
+fn clone_key(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
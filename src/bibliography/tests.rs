@@ -0,0 +1,539 @@
+use super::*;
+use crate::bcf::{Controlfile, OptionDatatype, ScopeType, ScopedOption};
+use crate::bibtex::Input;
+use crate::value::{Date, DatePoint, EntryOption, List, Name, OptionValue, Range};
+
+
+fn test_file(file: &str) -> std::path::PathBuf {
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("resources/test");
+    path.push(file);
+    path
+}
+
+/// The datamodel bundled as `Datamodel::default()`.
+fn default_datamodel_file() -> std::path::PathBuf {
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("resources/default-datamodel.bcf");
+    path
+}
+
+fn datamodel() -> Datamodel {
+    Controlfile::from_file(default_datamodel_file()).unwrap().datamodel
+}
+
+macro_rules! resolve {
+    ($($lines:expr),+ $(,)?) => {{
+        let input = Input::new("<internal test>", concat!($($lines, '\n'),+));
+        let input: &'static Input = Box::leak(Box::new(input));
+        let mut raw = RawBibliography::new();
+        raw.add_bibtex_resource(input).unwrap();
+        Bibliography::resolve(&raw, &datamodel())
+    }};
+}
+
+// --------------------------
+
+#[test]
+fn typed_fields() {
+    let (bib, errors) = resolve!(
+        "@Article{key,",
+        "  Author = {K. I. Aardal and van Hoesel, C. and {Ernst and Sons}},",
+        "  title = {The {CALMA} \\emph{project}},",
+        "  volume = 50, pages = {968 - 980}, month = JAN,",
+        "  date = {2003-06-21/2003-07},",
+        "  location = {Berlin and Paris}, related = {a, b},",
+        "}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let entry = bib.get("key").unwrap();
+    assert_eq!(entry.entrytype(), "article");
+    assert_eq!(entry.get("author").unwrap().name(), "Author");
+    assert_eq!(entry.get("author").unwrap().value(), &Value::Names(vec![
+        Name { family: "Aardal".into(), given: Some("K. I.".into()), ..Default::default() },
+        Name { family: "Hoesel".into(), given: Some("C.".into()), prefix: Some("van".into()), ..Default::default() },
+        Name { family: "{Ernst and Sons}".into(), ..Default::default() },
+    ].into()));
+    assert_eq!(entry.get("title").unwrap().value(), &Value::Literal("The {CALMA} \\emph{project}".into()));
+    assert_eq!(entry.get("volume").unwrap().value(), &Value::Integer(50));
+    assert_eq!(entry.get("month").unwrap().value(), &Value::Integer(1));
+    let Value::Range(pages) = entry.get("pages").unwrap().value() else { panic!() };
+    assert_eq!(pages.ranges(), &[Range::Closed("968".into(), "980".into())]);
+    assert_eq!(entry.get("date").unwrap().value(), &Value::Date(Date::Range(
+        DatePoint { year: 2003, month: Some(6), day: Some(21), ..Default::default() },
+        Some(DatePoint { year: 2003, month: Some(7), ..Default::default() }),
+    )));
+    assert_eq!(entry.get("location").unwrap().value(), &Value::Literals(vec!["Berlin".into(), "Paris".into()].into()));
+    assert_eq!(entry.get("related").unwrap().value(), &Value::Keys(vec!["a".into(), "b".into()].into()));
+}
+
+#[test]
+fn list_fields() {
+    let (bib, errors) = resolve!(
+        "@book{key,",
+        "  author = {Aardal, K. and van Hoesel, C. and others},",
+        "  publisher = {{Ernst and Sons} AND Wiley and others},",
+        "  location = {others},",
+        "  institution = {A and B and C and D},",
+        "}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let entry = bib.get("key").unwrap();
+    let Value::Names(authors) = entry.get("author").unwrap().value() else { panic!() };
+    assert_eq!(authors.len(), 2);
+    assert!(authors.more());
+    assert!(authors.truncated(3, 1));
+    assert_eq!(entry.get("publisher").unwrap().value(), &Value::Literals(List::new(vec!["{Ernst and Sons}".into(), "Wiley".into()], true)));
+    assert_eq!(entry.get("location").unwrap().value(), &Value::Literals(List::new(vec![], true)));
+
+    let Value::Literals(institutions) = entry.get("institution").unwrap().value() else { panic!() };
+    assert!(!institutions.more());
+    assert_eq!((institutions.visible(3, 1), institutions.truncated(3, 1)), (1, true));
+    assert_eq!((institutions.visible(4, 1), institutions.truncated(4, 1)), (4, false));
+    assert_eq!(institutions.visible(3, 2), 2);
+}
+
+#[test]
+fn extended_names() {
+    use crate::bcf::{ConstantValue, DatamodelBuilder};
+
+    let input = Input::new("<internal test>", "@book{key, author = {family=Smith, given=John, middle=Quincy, useprefix=true}}");
+    let mut raw = RawBibliography::new();
+    raw.add_bibtex_resource(&input).unwrap();
+    let author = |datamodel: &Datamodel| {
+        let (bib, errors) = Bibliography::resolve(&raw, datamodel);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let Value::Names(names) = bib.get("key").unwrap().get("author").unwrap().value() else { panic!() };
+        names.items()[0].clone()
+    };
+
+    let name = Name { family: "Smith".into(), given: Some("John".into()), ..Default::default() };
+    assert_eq!(author(&datamodel()), name);
+
+    let mut builder = DatamodelBuilder::from(datamodel());
+    let nameparts = ["family", "given", "middle", "prefix", "suffix"].map(String::from).to_vec();
+    builder.constant("nameparts", ConstantValue::List(nameparts));
+    assert_eq!(author(&builder.build()), Name { other_parts: vec![("middle".into(), "Quincy".into())], ..name });
+}
+
+#[test]
+fn verbatim_fields() {
+    let (bib, errors) = resolve!(
+        "@online{key,",
+        "  url = {http://example.org/M%C3%BCller\\%7E % not a comment},",
+        "  file = \"C:\\Users\\{\"}x{\"}\\\",",
+        "  doi = {10.1000/a_b%c  \\emph{x}},",
+        "  title = \"A \\{ \\} quote\",",
+        "}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let entry = bib.get("key").unwrap();
+    assert_eq!(entry.get("url").unwrap().value(), &Value::Uri("http://example.org/Müller~ % not a comment".into()));
+    assert_eq!(entry.get("file").unwrap().value(), &Value::Verbatim("C:\\Users\\{\"}x{\"}\\".into()));
+    assert_eq!(entry.get("doi").unwrap().value(), &Value::Verbatim("10.1000/a_b%c  \\emph{x}".into()));
+    assert_eq!(entry.get("title").unwrap().value(), &Value::Literal("A \\{ \\} quote".into()));
+}
+
+#[test]
+fn keywords_and_options() {
+    let (bib, errors) = resolve!(
+        "@book{key,",
+        "  keywords = { primary, , secondary,primary },",
+        "  options = {useprefix=true, skipbib, maxnames = 2, uniquename=init},",
+        "}",
+        "@book{invalid, options = {useprefix=maybe}}",
+        "@book{unknown, options = {nosuchoption}}",
+    );
+    let entry = bib.get("key").unwrap();
+    assert_eq!(entry.get("keywords").unwrap().value(), &Value::Keywords(vec!["primary".into(), "secondary".into()]));
+    assert!(entry.has_keyword("secondary"));
+    assert!(!entry.has_keyword("second"));
+    let Value::Options(options) = entry.get("options").unwrap().value() else { panic!() };
+    assert_eq!(options[0], EntryOption { name: "useprefix".into(), value: OptionValue::Boolean(true) });
+    assert_eq!(entry.option("skipbib"), Some(&OptionValue::Boolean(true)));
+    assert_eq!(entry.option("maxnames"), Some(&OptionValue::Integer(2)));
+    assert_eq!(entry.option("uniquename"), Some(&OptionValue::String("init".into())));
+    assert_eq!(entry.option("dataonly"), None);
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(bib.get("invalid").unwrap().option("useprefix"), Some(&OptionValue::String("maybe".into())));
+    assert_eq!(bib.get("unknown").unwrap().option("nosuchoption"), Some(&OptionValue::Boolean(true)));
+
+    // options declared by the style, e.g. with `\DeclareEntryOption`
+    let controlfile = Controlfile::from_file(test_file("full-controlfile.bcf")).unwrap();
+    let mut scope = controlfile.optionscope(ScopeType::Entry).unwrap().clone();
+    scope.option.push(ScopedOption { datatype: OptionDatatype::Integer, backendin: None, name: "maxnames".into() });
+    let warnings: Vec<String> = bib.validate_options(&scope).iter()
+        .map(|warning| warning.to_string().split_once("): ").unwrap().1.to_owned())
+        .collect();
+    assert_eq!(warnings, [
+        "Invalid value of entry option 'useprefix', must be a boolean",
+        "Unknown entry option 'uniquename'",
+        "Unknown entry option 'nosuchoption'",
+    ]);
+    assert!(bib.validate_options(&scope)[1].to_string().starts_with("Entry 'key' (<internal test>:3:"));
+}
+
+#[test]
+fn aliases() {
+    let (bib, errors) = resolve!(
+        "@PhdThesis{thesis, School = {ETH}, Address = {Zurich}}",
+        "@mastersthesis{typed, type = {Diploma thesis}}",
+        "@conference{conf, journal = {A}, journaltitle = {B}}",
+    );
+    let thesis = bib.get("thesis").unwrap();
+    assert_eq!(thesis.entrytype(), "thesis");
+    assert_eq!(thesis.get("type").unwrap().value(), &Value::Key("phdthesis".into()));
+    assert_eq!(thesis.get("institution").unwrap().value(), &Value::Literals(vec!["ETH".into()].into()));
+    assert_eq!(thesis.get("institution").unwrap().name(), "School");
+    assert_eq!(thesis.get("location").unwrap().value(), &Value::Literals(vec!["Zurich".into()].into()));
+    assert!(thesis.get("school").is_none());
+    assert_eq!(bib.get("typed").unwrap().get("type").unwrap().value(), &Value::Key("Diploma thesis".into()));
+
+    let conf = bib.get("conf").unwrap();
+    assert_eq!(conf.entrytype(), "inproceedings");
+    assert_eq!(conf.get("journaltitle").unwrap().value(), &Value::Literal("B".into()));
+    assert!(matches!(errors.as_slice(), [Error::DoubleField(name, _, _)] if name == "journaltitle"));
+
+    let mut aliases = Aliases::new();
+    aliases.alias_field("school", "publisher");
+    let input: &'static Input = Box::leak(Box::new(Input::new("<internal test>", "@phdthesis{key, school = {ETH}}")));
+    let mut raw = RawBibliography::new();
+    raw.add_bibtex_resource(input).unwrap();
+    let (bib, _) = Bibliography::resolve_with_aliases(&raw, &datamodel(), &aliases);
+    let entry = bib.get("key").unwrap();
+    assert_eq!(entry.entrytype(), "phdthesis");
+    assert!(entry.get("type").is_none());
+    assert!(entry.get("publisher").is_some());
+}
+
+#[test]
+fn crossrefs() {
+    let (mut bib, errors) = resolve!(
+        "@mvbook{mv, title = {Collected Works}, author = {Goethe, J. W.}, sorttitle = {Works}}",
+        "@book{book, crossref = {mv}, title = {Volume}, publisher = {Cotta}, volume = 2}",
+        "@inbook{inbook, crossref = {book}, title = {Chapter}, publisher = {Other}, options = {skipbib}}",
+        "@inbook{second, crossref = {book}}",
+        "@article{missing, crossref = {nowhere}}",
+        "@misc{loop1, crossref = {loop2}}",
+        "@misc{loop2, crossref = {loop1}}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (included, errors) = bib.resolve_crossrefs(&Inheritance::default(), 2);
+    assert_eq!(included, vec!["book".to_owned()]);
+
+    let book = bib.get("book").unwrap();
+    assert_eq!(book.get("maintitle").unwrap().value(), &Value::Literal("Collected Works".into()));
+    assert_eq!(book.get("title").unwrap().value(), &Value::Literal("Volume".into()));
+    assert!(book.get("sorttitle").is_none());
+    assert!(book.get("author").is_some());
+
+    let inbook = bib.get("inbook").unwrap();
+    assert_eq!(inbook.get("title").unwrap().value(), &Value::Literal("Chapter".into()));
+    assert_eq!(inbook.get("booktitle").unwrap().value(), &Value::Literal("Volume".into()));
+    assert_eq!(inbook.get("maintitle").unwrap().value(), &Value::Literal("Collected Works".into()));
+    assert_eq!(inbook.get("bookauthor").unwrap().value(), inbook.get("author").unwrap().value());
+    assert_eq!(inbook.get("publisher").unwrap().value(), &Value::Literals(vec!["Other".into()].into()));
+    assert_eq!(inbook.get("volume").unwrap().value(), &Value::Integer(2));
+    assert_eq!(inbook.get("crossref").unwrap().value(), &Value::Key("book".into()));
+    assert_eq!(inbook.get("options").unwrap().name(), "options");
+    assert!(bib.get("second").unwrap().get("options").is_none());
+
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    for error in errors {
+        match error {
+            Error::UndefinedEntry(key, trace) => assert_eq!((key.as_str(), trace.line), ("nowhere", 5)),
+            Error::CircularReference(key, _) => assert!(key.starts_with("loop")),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    let mut inheritance = Inheritance::default();
+    inheritance.defaults.override_target = true;
+    inheritance.rules.push(InheritanceRule::new(&["book"], &["inbook"], vec![FieldInheritance::new("publisher", None)]));
+    let (mut bib, _) = resolve!(
+        "@book{book, title = {Volume}, publisher = {Cotta}, note = {Parent}}",
+        "@inbook{inbook, crossref = {book}, title = {Chapter}, note = {Child}}",
+    );
+    let (included, _) = bib.resolve_crossrefs(&inheritance, 2);
+    assert!(included.is_empty());
+    let inbook = bib.get("inbook").unwrap();
+    assert_eq!(inbook.get("note").unwrap().value(), &Value::Literal("Parent".into()));
+    assert_eq!(inbook.get("title").unwrap().value(), &Value::Literal("Chapter".into()));
+    assert!(inbook.get("publisher").is_none());
+}
+
+#[test]
+fn xdata() {
+    let (bib, errors) = resolve!(
+        "@xdata{macmillan:name, publisher = {Macmillan and Penguin}}",
+        "@xdata{macmillan:place, location = {New York and London}}",
+        "@xdata{macmillan, xdata = {macmillan:name, macmillan:place}, note = {Note}}",
+        "@book{key, xdata = {macmillan}, note = {Own note},",
+        "  author = {Smith, John and xdata=macmillan-publisher-2},",
+        "  origlocation = {xdata=macmillan:place-location-1},",
+        "  title = {xdata=macmillan-note}}",
+        "@book{missing, xdata = {nowhere}, title = {xdata=macmillan-title}, series = {xdata=macmillan-location-3}}",
+        "@xdata{loop1, xdata = {loop2}}",
+        "@xdata{loop2, xdata = {loop1}}",
+    );
+    assert_eq!(bib.len(), 2);
+    assert!(bib.entries().all(|entry| entry.entrytype() != "xdata"));
+
+    let entry = bib.get("key").unwrap();
+    assert_eq!(entry.get("publisher").unwrap().value(), &Value::Literals(vec!["Macmillan".into(), "Penguin".into()].into()));
+    assert_eq!(entry.get("location").unwrap().value(), &Value::Literals(vec!["New York".into(), "London".into()].into()));
+    assert_eq!(entry.get("note").unwrap().value(), &Value::Literal("Own note".into()));
+    assert_eq!(entry.get("author").unwrap().value(), &Value::Names(vec![
+        Name { family: "Smith".into(), given: Some("John".into()), ..Default::default() },
+        Name { family: "Penguin".into(), ..Default::default() },
+    ].into()));
+    assert_eq!(entry.get("origlocation").unwrap().value(), &Value::Literals(vec!["New York".into()].into()));
+    assert_eq!(entry.get("title").unwrap().value(), &Value::Literal("Note".into()));
+    assert_eq!(entry.get("title").unwrap().raw().to_string(), "xdata=macmillan-note");
+
+    let missing = bib.get("missing").unwrap();
+    assert!(missing.get("title").is_none());
+    assert!(missing.get("series").is_none());
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    for error in errors {
+        match error {
+            Error::UndefinedEntry(key, trace) => assert_eq!((key.as_str(), trace.line), ("nowhere", 8)),
+            Error::UndefinedField(key, field, _) => assert!(key == "macmillan" && (field == "title" || field == "location")),
+            Error::CircularReference(key, trace) => assert!(key.starts_with("loop") && trace.line >= 9),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn sets() {
+    let (mut bib, errors) = resolve!(
+        "@set{set, entryset = {b, a, nowhere}}",
+        "@article{a, title = {A}}",
+        "@article{b, title = {B}}",
+        "@article{c, title = {C}}",
+        "@article{d, title = {D}}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    bib.add_dynamic_set("dynamic", vec!["d".into(), "c".into()]).unwrap();
+    assert!(matches!(bib.add_dynamic_set("a", vec![]), Err(Error::DoubleKey(key, _, _)) if key == "a"));
+
+    let errors = bib.resolve_sets();
+    assert!(matches!(errors.as_slice(), [Error::UndefinedEntry(key, trace)] if key == "nowhere" && trace.line == 1));
+    assert_eq!(bib.get("set").unwrap().entryset().unwrap(), &["b".to_owned(), "a".to_owned()]);
+    assert_eq!(bib.get("a").unwrap().inset(), Some("set"));
+    assert_eq!(bib.get("b").unwrap().inset(), Some("set"));
+    assert_eq!(bib.get("set").unwrap().inset(), None);
+
+    let dynamic = bib.get("dynamic").unwrap();
+    assert_eq!(dynamic.entrytype(), "set");
+    assert_eq!(dynamic.entryset().unwrap(), &["d".to_owned(), "c".to_owned()]);
+    assert_eq!((dynamic.trace().name.as_str(), dynamic.trace().line), ("<generated>", 0));
+    assert_eq!(dynamic.get("entryset").unwrap().name(), "entryset");
+    assert_eq!(bib.get("c").unwrap().inset(), Some("dynamic"));
+}
+
+#[test]
+fn related() {
+    let (mut bib, errors) = resolve!(
+        "@book{original, title = {Original}, related = {translation, nowhere}, relatedtype = {translatedas}}",
+        "@book{translation, title = {Translation}, relatedoptions = {dataonly, useeditor=false}, related = {original2}}",
+        "@book{original2, title = {Second}}",
+        "@book{pair1, title = {One}, related = {pair2}}",
+        "@book{pair2, title = {Two}, related = {pair1}}",
+        "@book{self, related = {self}}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let errors = bib.resolve_related();
+    let errors: Vec<(String, u32)> = errors.into_iter()
+        .map(|error| match error {
+            Error::UndefinedEntry(key, trace) => (format!("undefined {}", key), trace.line),
+            Error::CircularReference(key, trace) => (format!("circular {}", key), trace.line),
+            other => panic!("unexpected {:?}", other),
+        })
+        .collect();
+    assert_eq!(errors, [("circular pair1".to_owned(), 5), ("circular self".to_owned(), 6), ("undefined nowhere".to_owned(), 1)]);
+
+    let original = bib.get("original").unwrap();
+    let [clone_key] = original.related().unwrap() else { panic!() };
+    assert_eq!(original.get("relatedtype").unwrap().value(), &Value::Literal("translatedas".into()));
+    let clone = bib.get(clone_key).unwrap();
+    assert_eq!(clone.clone_source(), Some("translation"));
+    assert_eq!(clone.get("title").unwrap().value(), &Value::Literal("Translation".into()));
+    assert_eq!(clone.option("dataonly"), Some(&OptionValue::Boolean(true)));
+    assert!(clone.related().is_none());
+
+    // `translation` gets its own clone of `original2`, with its options
+    let [nested] = bib.get("translation").unwrap().related().unwrap() else { panic!() };
+    let nested = bib.get(nested).unwrap();
+    assert_eq!(nested.clone_source(), Some("original2"));
+    assert_eq!(nested.option("useeditor"), Some(&OptionValue::Boolean(false)));
+
+    // the relation closing the cycle is dropped
+    let [clone] = bib.get("pair1").unwrap().related().unwrap() else { panic!() };
+    assert_eq!(bib.get(clone).unwrap().clone_source(), Some("pair2"));
+    assert_eq!(bib.get("pair2").unwrap().related(), Some(&[][..]));
+    assert_eq!(bib.get("self").unwrap().related(), Some(&[][..]));
+    assert_eq!(bib.len(), 9);
+}
+
+#[test]
+fn ids() {
+    let (mut bib, errors) = resolve!(
+        "@book{a, ids = {old-a, older-a}}",
+        "@book{b, ids = {old-b, a}}",
+        "@book{c, ids = {old-a, c}}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let errors = bib.resolve_ids();
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    for error in errors {
+        match error {
+            Error::DoubleKey(key, first, second) if key == "a" => assert_eq!((first.line, second.line), (1, 2)),
+            Error::DoubleKey(key, first, second) if key == "old-a" => assert_eq!((first.line, second.line), (1, 3)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert_eq!(bib.canonical_key("older-a"), Some("a"));
+    assert_eq!(bib.canonical_key("old-a"), Some("a"));
+    assert_eq!(bib.canonical_key("old-b"), Some("b"));
+    assert_eq!(bib.canonical_key("c"), Some("c"));
+    assert_eq!(bib.canonical_key("d"), None);
+    bib.remove("b");
+    assert_eq!(bib.canonical_key("old-b"), None);
+}
+
+#[test]
+fn type_errors() {
+    let (bib, errors) = resolve!(
+        "@book{key,",
+        "  volume = {19A},",
+        "  date = {2003-13},",
+        "  title = undefined,",
+        "  year = {1992--1993},",
+        "  volumes = {iiiiiiiiiiix},",
+        "  note = ümacro,",
+        "}",
+    );
+    let entry = bib.get("key").unwrap();
+    assert_eq!(entry.get("volume").unwrap().value(), &Value::Literal("19A".into()));
+    assert_eq!(entry.get("date").unwrap().value(), &Value::Literal("2003-13".into()));
+    assert_eq!(entry.get("year").unwrap().value(), &Value::Literal("1992--1993".into()));
+    assert_eq!(entry.get("volumes").unwrap().value(), &Value::Literal("iiiiiiiiiiix".into()));
+    assert_eq!(errors.len(), 5);
+    for error in errors {
+        match error {
+            Error::InvalidValue(field, _, trace) if field == "volume" => assert_eq!(trace.line, 2),
+            Error::InvalidValue(field, _, trace) if field == "date" => assert_eq!(trace.line, 3),
+            Error::InvalidValue(field, _, trace) if field == "volumes" => assert_eq!(trace.line, 6),
+            Error::UndefinedMacro(name, trace) if name == "undefined" => assert_eq!(trace.line, 4),
+            Error::UndefinedMacro(name, trace) if name == "ümacro" => assert_eq!((trace.line, trace.col), (7, 10)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn validate() {
+    let (bib, errors) = resolve!(
+        "@article{valid, author = {A}, title = {T}, journaltitle = {J}, date = 2000,",
+        "  issn = {0378-5955}, gender = {sf}}",
+        "@book{book, title = {T}, year = 2000, date = 2000, journaltitle = {J},",
+        "  isbn = {0-306-40615-3}, gender = {x}}",
+        "@booklet{booklet, title = {T}, eventdate = 2000}",
+        "@foo{foo, title = {T}}",
+        "@inbook{inbook, author = {A}, title = {T}, booktitle = {B}, date = 2000,",
+        "  isbn = {978-3-16-148410-0}}",
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    let warnings = bib.validate(&datamodel());
+    let messages: Vec<String> = warnings.iter()
+        .map(|warning| format!("{} {}", warning.trace().line, warning.to_string().split_once("): ").unwrap().1))
+        .collect();
+    assert_eq!(messages, [
+        "3 Invalid field 'journaltitle' for entrytype 'book'",
+        "3 Mandatory fields - only one of 'date, year' must be defined",
+        "3 Missing mandatory field 'author'",
+        "4 Invalid value of field 'isbn' (invalid ISBN '0-306-40615-3')",
+        "4 Invalid value of field 'gender' (does not match '(?:sf|sm|sn|pf|pm|pn|pp)')",
+        "5 Invalid field 'eventdate' for entrytype 'booklet'",
+        "5 Missing mandatory field - one of 'date, year' must be defined",
+        "5 Missing mandatory field - one of 'author, editor' must be defined",
+        "6 Invalid entry type 'foo'",
+    ]);
+    assert_eq!(warnings[0].key(), "book");
+    assert_eq!(
+        warnings[8].to_string(),
+        "Datamodel: Entry 'foo' (<internal test>:6:6): Invalid entry type 'foo'",
+    );
+
+    // without the pattern biblatex declares, the `gender` constant is checked
+    let gender = r#"
+      <bcf:constraint type="data" datatype="pattern" pattern="(?:sf|sm|sn|pf|pm|pn|pp)">
+        <bcf:field>gender</bcf:field>
+      </bcf:constraint>"#;
+    let xml = std::fs::read_to_string(default_datamodel_file()).unwrap().replace(gender, "");
+    let datamodel = xml.parse::<Controlfile>().unwrap().datamodel;
+    let gender: Vec<String> = bib.validate(&datamodel).into_iter()
+        .filter(|warning| matches!(warning, Warning::InvalidData(_, field, ..) if field == "gender"))
+        .map(|warning| warning.to_string().split_once("): ").unwrap().1.to_owned())
+        .collect();
+    assert_eq!(gender, ["Invalid value of field 'gender' (invalid gender 'x', must be one of 'sf, sm, sn, pf, pm, pn, pp')"]);
+}
+
+#[test]
+fn validate_constraints() {
+    let constraints = r#"
+    <bcf:constraints>
+      <bcf:constraint type="data" datatype="integer" rangemin="1" rangemax="12"><bcf:field>volume</bcf:field></bcf:constraint>
+      <bcf:constraint type="data" datatype="pattern" pattern="[A-Z]+"><bcf:field>venue</bcf:field></bcf:constraint>
+      <bcf:constraint type="data" datatype="pattern" pattern="(["><bcf:field>eventtitle</bcf:field></bcf:constraint>
+      <bcf:constraint type="conditional">
+        <bcf:antecedent quant="one"><bcf:field>eventtitle</bcf:field><bcf:field>venue</bcf:field></bcf:antecedent>
+        <bcf:consequent quant="all"><bcf:field>eventdate</bcf:field></bcf:consequent>
+      </bcf:constraint>
+    </bcf:constraints>
+  </bcf:datamodel>"#;
+    let xml = std::fs::read_to_string(default_datamodel_file()).unwrap()
+        .replace("\n  </bcf:datamodel>", constraints);
+    let datamodel = xml.parse::<Controlfile>().unwrap().datamodel;
+    let (bib, _) = resolve!(
+        "@proceedings{a, editor = {E}, title = {T}, date = 2000, volume = 13, venue = {V}, eventtitle = {E}}",
+        "@proceedings{b, editor = {E}, title = {T}, date = 2000, volume = 12, venue = {v}, eventdate = 1999,",
+        "  eventtitle = {E}}",
+    );
+    let messages: Vec<String> = bib.validate(&datamodel).iter()
+        .map(|warning| warning.to_string().split_once("): ").unwrap().1.to_owned())
+        .collect();
+    assert_eq!(messages, [
+        "Invalid value of field 'volume' (must be <= 12)",
+        "Invalid value of field 'eventtitle' (invalid pattern '([' in the datamodel, not checked)",
+        "Constraint violation - all of fields (eventdate) must exist when one of fields (eventtitle, venue) exist",
+        "Invalid value of field 'venue' (does not match '[A-Z]+')",
+    ]);
+}
+
+#[test]
+fn biber_benchmark() {
+    let inputs = vec![
+        Input::from_file(test_file("biber-benchmark-definitions.bib")).unwrap(),
+        Input::from_file(test_file("biber-benchmark-papers.bib")).unwrap(),
+    ];
+    let mut raw = RawBibliography::new();
+    for input in &inputs {
+        raw.add_bibtex_resource(input).unwrap();
+    }
+    let (bib, errors) = Bibliography::resolve(&raw, &datamodel());
+    assert_eq!(bib.len(), 2157);
+    // non-numeric volumes like `{E78-A}`
+    assert_eq!(errors.len(), 12, "{:#?}", errors);
+    assert!(errors.iter().all(|error| matches!(error, Error::InvalidValue(field, _, _) if field == "volume")));
+    let volumes = bib.entries().filter_map(|entry| entry.get("volume")).filter(|volume| matches!(volume.value(), Value::Literal(_)));
+    assert_eq!(volumes.count(), 12);
+
+    let entry = bib.get("AardalHLT02").unwrap();
+    assert_eq!(entry.get("journaltitle").unwrap().value(), &Value::Literal("Operations Research".into()));
+    let Value::Range(pages) = entry.get("pages").unwrap().value() else { panic!() };
+    assert_eq!(pages.normalised(), "968\\bibrangedash 980");
+}
@@ -0,0 +1,367 @@
+use std::collections::hash_map::{self, HashMap};
+use std::fmt;
+
+use regex::Regex;
+
+use super::{Bibliography, Entry, Field};
+use crate::bcf::{
+    Condition, Constraint, ConstraintItem, ConstraintType, DataType, Datamodel, Mandatory, OptionDatatype, OptionScope,
+    Quantifier,
+};
+use crate::bibtex::InputTrace;
+use crate::value::{OptionValue, Value};
+
+
+/// Violation of the datamodel by an entry, with the entry key first. Like
+/// biber, these are warnings: the entry is kept as it is.
+#[derive(Debug)]
+pub enum Warning {
+    /// Entry type unknown to the datamodel.
+    InvalidEntrytype(String, String, InputTrace),
+    /// Field and entry type, if `entryfields` do not allow the field.
+    InvalidField(String, String, String, InputTrace),
+    MissingField(String, String, InputTrace),
+    /// None of the fields of a `fieldor` or `fieldxor` group is present.
+    MissingOneOf(String, Vec<String>, InputTrace),
+    /// More than one field of a `fieldxor` group is present.
+    ExclusiveFields(String, Vec<String>, InputTrace),
+    /// Field and reason of a failed `data` constraint.
+    InvalidData(String, String, String, InputTrace),
+    /// Consequent and antecedent of a failed `conditional` constraint.
+    Conditional(String, (Quantifier, Vec<String>), (Quantifier, Vec<String>), InputTrace),
+    /// Option of the `options` field the style does not declare.
+    UnknownOption(String, String, InputTrace),
+    /// Option of the `options` field with a value not of its datatype.
+    InvalidOption(String, String, OptionDatatype, InputTrace),
+}
+
+impl Warning {
+
+    pub fn key(&self) -> &str {
+        match self {
+            Warning::InvalidEntrytype(key, ..)
+            | Warning::InvalidField(key, ..)
+            | Warning::MissingField(key, ..)
+            | Warning::MissingOneOf(key, ..)
+            | Warning::ExclusiveFields(key, ..)
+            | Warning::InvalidData(key, ..)
+            | Warning::Conditional(key, ..)
+            | Warning::UnknownOption(key, ..)
+            | Warning::InvalidOption(key, ..) => key,
+        }
+    }
+
+    pub fn trace(&self) -> &InputTrace {
+        match self {
+            Warning::InvalidEntrytype(.., trace)
+            | Warning::InvalidField(.., trace)
+            | Warning::MissingField(.., trace)
+            | Warning::MissingOneOf(.., trace)
+            | Warning::ExclusiveFields(.., trace)
+            | Warning::InvalidData(.., trace)
+            | Warning::Conditional(.., trace)
+            | Warning::UnknownOption(.., trace)
+            | Warning::InvalidOption(.., trace) => trace,
+        }
+    }
+}
+
+/// In the words of biber, e.g. `Datamodel: Entry 'key' (file.bib:3:2):
+/// Missing mandatory field 'author'`.
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let trace = self.trace();
+        if !matches!(self, Warning::UnknownOption(..) | Warning::InvalidOption(..)) {
+            f.write_str("Datamodel: ")?;
+        }
+        write!(f, "Entry '{}' ({}:{}:{}): ", self.key(), trace.name, trace.line, trace.col)?;
+        match self {
+            Warning::InvalidEntrytype(_, entrytype, _) => write!(f, "Invalid entry type '{}'", entrytype),
+            Warning::InvalidField(_, field, entrytype, _) => write!(f, "Invalid field '{}' for entrytype '{}'", field, entrytype),
+            Warning::MissingField(_, field, _) => write!(f, "Missing mandatory field '{}'", field),
+            Warning::MissingOneOf(_, fields, _) => write!(f, "Missing mandatory field - one of '{}' must be defined", fields.join(", ")),
+            Warning::ExclusiveFields(_, fields, _) => write!(f, "Mandatory fields - only one of '{}' must be defined", fields.join(", ")),
+            Warning::InvalidData(_, field, reason, _) => write!(f, "Invalid value of field '{}' ({})", field, reason),
+            Warning::Conditional(_, (cquant, cfields), (aquant, afields), _) => write!(
+                f, "Constraint violation - {} of fields ({}) must exist when {} of fields ({}) exist",
+                cquant.name(), cfields.join(", "), aquant.name(), afields.join(", "),
+            ),
+            Warning::UnknownOption(_, option, _) => write!(f, "Unknown entry option '{}'", option),
+            Warning::InvalidOption(_, option, datatype, _) => {
+                let expected = match datatype {
+                    OptionDatatype::Boolean => "a boolean",
+                    OptionDatatype::Integer => "an integer",
+                    OptionDatatype::String | OptionDatatype::Xml => "a string",
+                };
+                write!(f, "Invalid value of entry option '{}', must be {}", option, expected)
+            },
+        }
+    }
+}
+
+
+impl Bibliography<'_> {
+
+    /// Checks every entry against `datamodel`: its entry type, whether its
+    /// fields are allowed for the entry type, and the `mandatory` and `data`
+    /// constraints. Run after inheritance, as biber does. Warnings are ordered
+    /// by entry key. A pattern that does not compile is reported at the first
+    /// field it applies to and not checked.
+    pub fn validate(&self, datamodel: &Datamodel) -> Vec<Warning> {
+        let mut entries: Vec<&Entry> = self.entries.values().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut warnings = Vec::new();
+        let mut patterns = Patterns::new();
+        for entry in entries {
+            entry.validate(datamodel, &mut patterns, &mut warnings);
+        }
+        warnings
+    }
+
+    /// Checks the `options` field of every entry against the options the
+    /// style declares per entry, usually
+    /// `controlfile.optionscope(ScopeType::Entry)`. Unknown options and values
+    /// of the wrong type are reported and kept. Warnings are ordered by entry
+    /// key.
+    pub fn validate_options(&self, scope: &OptionScope) -> Vec<Warning> {
+        let mut entries: Vec<&Entry> = self.entries.values().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut warnings = Vec::new();
+        for entry in entries {
+            entry.check_options(scope, &mut warnings);
+        }
+        warnings
+    }
+}
+
+impl Entry<'_> {
+
+    fn validate(&self, datamodel: &Datamodel, patterns: &mut Patterns, warnings: &mut Vec<Warning>) {
+        if !datamodel.is_entrytype(&self.entrytype) {
+            warnings.push(Warning::InvalidEntrytype(self.key.clone(), self.entrytype.clone(), self.trace()));
+            return;
+        }
+
+        let mut fields: Vec<(&String, &Field)> = self.fields.iter().collect();
+        fields.sort_by_key(|(name, _)| name.as_str());
+        for (name, field) in fields {
+            // biber splits dates into parts, which are listed instead
+            let allowed_as = match datamodel.field(name) {
+                Some(def) if def.datatype == DataType::Date => name.strip_suffix("date").map(|prefix| format!("{}year", prefix)),
+                _ => None,
+            };
+            if !datamodel.is_field_for_entrytype(&self.entrytype, allowed_as.as_deref().unwrap_or(name)) {
+                warnings.push(Warning::InvalidField(self.key.clone(), name.clone(), self.entrytype.clone(), field.trace()));
+            }
+        }
+
+        for mandatory in datamodel.mandatory_fields(&self.entrytype) {
+            self.check_mandatory(mandatory, warnings);
+        }
+        for constraint in datamodel.constraints(&self.entrytype) {
+            match constraint.r#type {
+                ConstraintType::Mandatory => (),
+                ConstraintType::Data => self.check_data(constraint, patterns, warnings),
+                ConstraintType::Conditional => self.check_conditional(constraint, warnings),
+            }
+        }
+        self.check_gender(datamodel, warnings);
+    }
+
+    fn check_options(&self, scope: &OptionScope, warnings: &mut Vec<Warning>) {
+        let Some(field) = self.fields.get("options") else { return };
+        let Value::Options(options) = field.value() else { return };
+        for option in options {
+            let Some(declared) = scope.option.iter().find(|declared| declared.name.eq_ignore_ascii_case(&option.name)) else {
+                warnings.push(Warning::UnknownOption(self.key.clone(), option.name.clone(), field.trace()));
+                continue;
+            };
+            let valid = match declared.datatype {
+                OptionDatatype::Boolean => matches!(option.value, OptionValue::Boolean(_)),
+                OptionDatatype::Integer => matches!(option.value, OptionValue::Integer(_)),
+                OptionDatatype::String | OptionDatatype::Xml => true,
+            };
+            if !valid {
+                warnings.push(Warning::InvalidOption(self.key.clone(), option.name.clone(), declared.datatype, field.trace()));
+            }
+        }
+    }
+
+    fn check_mandatory(&self, mandatory: &Mandatory, warnings: &mut Vec<Warning>) {
+        match mandatory {
+            Mandatory::Field(field) if !self.fields.contains_key(field) => {
+                warnings.push(Warning::MissingField(self.key.clone(), field.clone(), self.trace()));
+            },
+            Mandatory::AnyOf(fields) if !fields.iter().any(|field| self.fields.contains_key(field)) => {
+                warnings.push(Warning::MissingOneOf(self.key.clone(), fields.clone(), self.trace()));
+            },
+            Mandatory::OneOf(fields) => {
+                let present: Vec<String> = fields.iter().filter(|field| self.fields.contains_key(*field)).cloned().collect();
+                if present.is_empty() {
+                    warnings.push(Warning::MissingOneOf(self.key.clone(), fields.clone(), self.trace()));
+                } else if present.len() > 1 {
+                    warnings.push(Warning::ExclusiveFields(self.key.clone(), present, self.trace()));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn check_data(&self, constraint: &Constraint, patterns: &mut Patterns, warnings: &mut Vec<Warning>) {
+        for item in &constraint.items {
+            let ConstraintItem::Field(name) = item else { continue };
+            let Some(field) = self.fields.get(name) else { continue };
+            let regex = match (constraint.datatype.as_deref(), constraint.pattern.as_deref()) {
+                (Some("pattern"), pattern) => match patterns.entry(pattern.unwrap_or("").to_owned()) {
+                    hash_map::Entry::Occupied(compiled) => compiled.into_mut().as_ref(),
+                    hash_map::Entry::Vacant(vacant) => {
+                        let regex = Regex::new(&format!("^(?:{})$", vacant.key())).ok();
+                        if regex.is_none() {
+                            let reason = format!("invalid pattern '{}' in the datamodel, not checked", vacant.key());
+                            warnings.push(Warning::InvalidData(self.key.clone(), name.clone(), reason, field.trace()));
+                        }
+                        vacant.insert(regex).as_ref()
+                    },
+                },
+                _ => None,
+            };
+            if let Err(reason) = check_value(constraint, regex, &constraint_text(field)) {
+                warnings.push(Warning::InvalidData(self.key.clone(), name.clone(), reason, field.trace()));
+            }
+        }
+    }
+
+    /// The `gender` field takes one of the datamodel's `gender` constant,
+    /// unless a `data` constraint checks it already.
+    fn check_gender(&self, datamodel: &Datamodel, warnings: &mut Vec<Warning>) {
+        let Some(field) = self.fields.get("gender") else { return };
+        let constrained = datamodel.constraints(&self.entrytype)
+            .filter(|constraint| constraint.r#type == ConstraintType::Data)
+            .any(|constraint| constraint.items.iter().any(|item| matches!(item, ConstraintItem::Field(name) if name == "gender")));
+        if constrained {
+            return;
+        }
+        let gender = constraint_text(field);
+        if !datamodel.genders().contains(&gender) {
+            let reason = format!("invalid gender '{}', must be one of '{}'", gender, datamodel.genders().join(", "));
+            warnings.push(Warning::InvalidData(self.key.clone(), "gender".to_owned(), reason, field.trace()));
+        }
+    }
+
+    fn check_conditional(&self, constraint: &Constraint, warnings: &mut Vec<Warning>) {
+        let condition = |wanted: fn(&ConstraintItem) -> Option<&Condition>| constraint.items.iter().find_map(wanted);
+        let antecedent = condition(|item| match item { ConstraintItem::Antecedent(c) => Some(c), _ => None });
+        let consequent = condition(|item| match item { ConstraintItem::Consequent(c) => Some(c), _ => None });
+        let (Some(antecedent), Some(consequent)) = (antecedent, consequent) else { return };
+        if self.satisfies(antecedent) && !self.satisfies(consequent) {
+            warnings.push(Warning::Conditional(
+                self.key.clone(),
+                (consequent.quant, consequent.field.clone()),
+                (antecedent.quant, antecedent.field.clone()),
+                self.trace(),
+            ));
+        }
+    }
+
+    fn satisfies(&self, condition: &Condition) -> bool {
+        let mut present = condition.field.iter().map(|field| self.fields.contains_key(field));
+        match condition.quant {
+            Quantifier::All => present.all(|present| present),
+            Quantifier::One => present.any(|present| present),
+            Quantifier::None => !present.any(|present| present),
+        }
+    }
+}
+
+
+// Data constraints
+// ----------------
+
+/// Compiled patterns of `data` constraints, `None` for those that do not
+/// compile.
+type Patterns = HashMap<String, Option<Regex>>;
+
+/// The value a `data` constraint applies to.
+fn constraint_text(field: &Field) -> String {
+    match field.value() {
+        Value::Integer(number) => number.to_string(),
+        Value::Literal(text) | Value::Key(text) => text.clone(),
+        _ => field.raw().to_string().trim().to_owned(),
+    }
+}
+
+/// Dates are already checked when typing the field. `regex` is the compiled
+/// pattern of a `pattern` constraint.
+fn check_value(constraint: &Constraint, regex: Option<&Regex>, text: &str) -> Result<(), String> {
+    let pattern = constraint.pattern.as_deref().unwrap_or("");
+    match constraint.datatype.as_deref().unwrap_or("") {
+        "integer" | "datepart" => {
+            let number: i64 = text.parse().map_err(|_| format!("'{}' is not an integer", text))?;
+            if let Some(min) = constraint.rangemin.filter(|min| number < *min) {
+                return Err(format!("must be >= {}", min));
+            }
+            if let Some(max) = constraint.rangemax.filter(|max| number > *max) {
+                return Err(format!("must be <= {}", max));
+            }
+            Ok(())
+        },
+        "isbn" if !is_isbn(text) => Err(format!("invalid ISBN '{}'", text)),
+        "issn" if !is_issn(text) => Err(format!("invalid ISSN '{}'", text)),
+        "ismn" if !is_ismn(text) => Err(format!("invalid ISMN '{}'", text)),
+        "pattern" => match regex {
+            Some(regex) if !regex.is_match(text) => Err(format!("does not match '{}'", pattern)),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Digits with hyphens and spaces removed, an `X` check digit as 10.
+fn digits(text: &str) -> Option<Vec<u32>> {
+    let chars: Vec<char> = text.chars().filter(|c| !matches!(c, '-' | ' ')).collect();
+    chars.iter().enumerate()
+        .map(|(i, c)| match c {
+            'X' | 'x' if i == chars.len() - 1 => Some(10),
+            _ => c.to_digit(10),
+        })
+        .collect()
+}
+
+fn is_ean13(digits: &[u32]) -> bool {
+    digits.len() == 13 && digits.iter().all(|d| *d < 10)
+        && digits.iter().enumerate().map(|(i, d)| if i % 2 == 0 { *d } else { 3 * d }).sum::<u32>() % 10 == 0
+}
+
+fn is_isbn(text: &str) -> bool {
+    match digits(text) {
+        Some(digits) if digits.len() == 10 => {
+            digits[..9].iter().all(|d| *d < 10)
+                && digits.iter().enumerate().map(|(i, d)| (10 - i as u32) * d).sum::<u32>() % 11 == 0
+        },
+        Some(digits) => is_ean13(&digits),
+        None => false,
+    }
+}
+
+fn is_issn(text: &str) -> bool {
+    match digits(text) {
+        Some(digits) if digits.len() == 8 => {
+            digits[..7].iter().all(|d| *d < 10)
+                && digits.iter().enumerate().map(|(i, d)| (8 - i as u32) * d).sum::<u32>() % 11 == 0
+        },
+        _ => false,
+    }
+}
+
+/// Both `M`-prefixed ten character and `979-0` thirteen digit ISMNs.
+fn is_ismn(text: &str) -> bool {
+    let text = text.trim();
+    let full = match text.strip_prefix(['M', 'm']) {
+        Some(rest) => format!("9790{}", rest),
+        None => text.to_owned(),
+    };
+    match digits(&full) {
+        Some(digits) => digits.starts_with(&[9, 7, 9, 0]) && is_ean13(&digits),
+        None => false,
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::bcf::{Datamodel, FieldType};
+use crate::bibtex::{Error, InputSlice};
+use crate::value::{split_and, split_xsv};
+use super::Draft;
+
+
+/// Prefix of granular references like `xdata=key-field-2`.
+const MARKER: &str = "xdata=";
+
+/// Resolves `xdata` fields and granular XDATA references of all entries.
+pub(super) fn resolve(drafts: &mut HashMap<String, Draft<'_>>, datamodel: &Datamodel, errors: &mut Vec<Error>) {
+    let mut resolver = Resolver { datamodel, done: HashSet::new(), stack: Vec::new(), errors };
+    let keys: Vec<String> = drafts.keys().cloned().collect();
+    for key in &keys {
+        resolver.entry(drafts, key);
+    }
+}
+
+
+struct Resolver<'a> {
+    datamodel: &'a Datamodel,
+    done: HashSet<String>,
+    /// Entries being resolved, for cycle detection.
+    stack: Vec<String>,
+    errors: &'a mut Vec<Error>,
+}
+
+impl Resolver<'_> {
+
+    /// Returns `false` if `key` is already being resolved.
+    fn entry(&mut self, drafts: &mut HashMap<String, Draft<'_>>, key: &str) -> bool {
+        if self.done.contains(key) {
+            return true;
+        }
+        if self.stack.iter().any(|other| other == key) {
+            return false;
+        }
+        self.stack.push(key.to_owned());
+
+        if let Some(field) = drafts[key].fields.get("xdata") {
+            let at = field.name.clone();
+            let references: Vec<String> = split_xsv(&field.content).into_iter().map(str::to_owned).collect();
+            for reference in references {
+                if !self.source(drafts, &reference, &at) {
+                    continue;
+                }
+                let inherited: Vec<_> = drafts[&reference].fields.iter()
+                    .filter(|(name, _)| !matches!(name.as_str(), "ids" | "xdata"))
+                    .map(|(name, field)| (name.clone(), field.clone()))
+                    .collect();
+                let draft = drafts.get_mut(key).unwrap();
+                for (name, field) in inherited {
+                    draft.fields.entry(name).or_insert(field);
+                }
+            }
+        }
+
+        let granular: Vec<String> = drafts[key].fields.iter()
+            .filter(|(_, field)| field.content.contains(MARKER))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in granular {
+            let field = &drafts[key].fields[&name];
+            let (content, at) = (field.content.clone(), field.name.clone());
+            let is_list = self.datamodel.field(&name).is_some_and(|def| def.fieldtype == FieldType::List);
+            let resolved = if is_list {
+                split_and(&content).into_iter()
+                    .map(|item| match item.strip_prefix(MARKER) {
+                        Some(reference) => self.lookup(drafts, reference, &at),
+                        None => Some(item.to_owned()),
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(|items| items.join(" and "))
+            } else {
+                match content.trim().strip_prefix(MARKER) {
+                    Some(reference) => self.lookup(drafts, reference, &at),
+                    None => Some(content),
+                }
+            };
+            let draft = drafts.get_mut(key).unwrap();
+            match resolved {
+                Some(content) => draft.fields.get_mut(&name).unwrap().content = content,
+                None => {
+                    draft.fields.remove(&name);
+                },
+            }
+        }
+
+        self.stack.pop();
+        self.done.insert(key.to_owned());
+        true
+    }
+
+    /// Makes sure `key` is a resolved XDATA entry.
+    fn source(&mut self, drafts: &mut HashMap<String, Draft<'_>>, key: &str, at: &InputSlice) -> bool {
+        if drafts.get(key).is_none_or(|draft| draft.entrytype != "xdata") {
+            self.errors.push(Error::UndefinedEntry(key.to_owned(), at.trace()));
+            return false;
+        }
+        if !self.entry(drafts, key) {
+            self.errors.push(Error::CircularReference(key.to_owned(), at.trace()));
+            return false;
+        }
+        true
+    }
+
+    /// Content of a reference `key-field` or `key-field-n`, where `n` counts
+    /// the items of a list field from `1`. Keys may contain `-` themselves.
+    fn lookup(&mut self, drafts: &mut HashMap<String, Draft<'_>>, reference: &str, at: &InputSlice) -> Option<String> {
+        let (rest, index) = match reference.rsplit_once('-') {
+            Some((rest, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => (rest, index.parse::<usize>().ok()),
+            _ => (reference, None),
+        };
+        let Some((key, name)) = rest.rsplit_once('-') else {
+            self.errors.push(Error::UndefinedEntry(reference.to_owned(), at.trace()));
+            return None;
+        };
+        if !self.source(drafts, key, at) {
+            return None;
+        }
+        let content = drafts[key].fields.get(name).and_then(|field| match index {
+            None => Some(field.content.clone()),
+            Some(index) => split_and(&field.content).get(index.checked_sub(1)?).map(|item| item.to_string()),
+        });
+        if content.is_none() {
+            self.errors.push(Error::UndefinedField(key.to_owned(), name.to_owned(), at.trace()));
+        }
+        content
+    }
+}
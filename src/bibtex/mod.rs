@@ -2,6 +2,8 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use crate::latex::Text;
+
 
 mod parse;
 
@@ -13,6 +15,13 @@ pub struct Input<'de> {
 }
 
 impl<'de> Input<'de> {
+    pub fn new<N: Into<Cow<'de, str>>, C: Into<Cow<'de, str>>>(name: N, content: C) -> Self {
+        Input {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
     pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, std::io::Error> {
         Ok(Input {
             name: Cow::Owned(path.as_ref().to_string_lossy().into_owned()),
@@ -21,23 +30,33 @@ impl<'de> Input<'de> {
     }
 
     fn trace(&self, offset: usize) -> InputTrace {
-        // Line number and start of line index
-        let mut line = 1;
-        let mut start = 0;
-        // scan all linebreaks 
-        for pos in 0..=offset {
-            if let Some(b'\n') = self.content.as_bytes().get(pos) {
-                line += 1;
-                start = pos+1;
-            }
+        // offsets inside a multibyte character point to the character
+        let mut offset = offset.min(self.content.len());
+        while !self.content.is_char_boundary(offset) {
+            offset -= 1;
         }
+        let before = &self.content[..offset];
+        // Line number and start of line index
+        let line = before.bytes().filter(|byte| *byte == b'\n').count() as u32 + 1;
+        let start = before.rfind('\n').map_or(0, |pos| pos + 1);
         // count unicode chars in line content
-        let col = self.content[start..=offset].chars().count() as u32;
+        let col = before[start..].chars().count() as u32 + 1;
         // full human-friendly trace information
         InputTrace{name: self.name.clone().into_owned(), line, col}
     }
+
+    /// The whole content as a slice.
+    pub fn slice(&'de self) -> InputSlice<'de> {
+        InputSlice {
+            r#str: &self.content,
+            input: Some(self),
+            offset: 0,
+        }
+    }
 }
 
+/// Location in an input. Text made up by the backend, like dynamic sets, has
+/// the name `<generated>` and line and column 0.
 #[derive(Debug)]
 pub struct InputTrace {
     pub name: String,
@@ -46,20 +65,50 @@ pub struct InputTrace {
 }
 
 #[derive(Debug, Clone)]
-struct InputSlice<'de> {
+pub struct InputSlice<'de> {
     r#str: &'de str,
-    input: &'de Input<'de>,
+    /// `None` for generated text.
+    input: Option<&'de Input<'de>>,
     offset: usize,
 }
 
 impl<'de> InputSlice<'de> {
 
-    fn to_string(&self) -> String {
-        self.str.to_owned()
+    /// Text made up by the backend instead of read from an input.
+    pub(crate) fn generated(text: &'de str) -> Self {
+        InputSlice { r#str: text, input: None, offset: 0 }
+    }
+
+    pub fn as_str(&self) -> &'de str {
+        self.str
+    }
+
+    pub fn trace(&self) -> InputTrace {
+        match self.input {
+            Some(input) => input.trace(self.offset),
+            None => InputTrace { name: "<generated>".to_owned(), line: 0, col: 0 },
+        }
+    }
+
+    /// Byte offset in the input.
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Sub-slice for a byte range of this slice.
+    pub(crate) fn slice(&self, range: std::ops::Range<usize>) -> InputSlice<'de> {
+        InputSlice {
+            r#str: &self.str[range.clone()],
+            input: self.input,
+            offset: self.offset + range.start,
+        }
     }
+}
+
 
-    fn trace(&self) -> InputTrace {
-        self.input.trace(self.offset)
+impl std::fmt::Display for InputSlice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.str)
     }
 }
 
@@ -75,6 +124,13 @@ pub enum Error{
     DoubleField(String, InputTrace, InputTrace),
     DoubleMacro(String, InputTrace, InputTrace),
     RecursiveMacro(String, InputTrace),
+    UndefinedMacro(String, InputTrace),
+    InvalidValue(String, String, InputTrace),
+    /// Reference to a missing entry, with the trace of the referencing field.
+    UndefinedEntry(String, InputTrace),
+    /// Entry key and field name of a reference to a missing field.
+    UndefinedField(String, String, InputTrace),
+    CircularReference(String, InputTrace),
 }
 
 #[derive(Debug)]
@@ -86,8 +142,8 @@ pub enum TokenContext {
 }
 
 
-#[derive(Debug)]
-enum RawValue<'de> {
+#[derive(Debug, Clone)]
+pub(crate) enum RawValue<'de> {
     Simple(InputSlice<'de>),
     Macro(InputSlice<'de>),
     Compound(Vec<Self>),
@@ -95,15 +151,15 @@ enum RawValue<'de> {
 
 #[derive(Debug)]
 pub struct FieldDef<'de> {
-    name: InputSlice<'de>,
-    value: RawValue<'de>,
+    pub(crate) name: InputSlice<'de>,
+    pub(crate) value: RawValue<'de>,
 }
 
 #[derive(Debug)]
 pub struct RawEntry<'de> {
-    entrytype: InputSlice<'de>,
-    key: InputSlice<'de>,
-    fields: HashMap<&'de str, FieldDef<'de>>
+    pub(crate) entrytype: InputSlice<'de>,
+    pub(crate) key: InputSlice<'de>,
+    pub(crate) fields: HashMap<&'de str, FieldDef<'de>>
 }
 
 type MacroList<'de> = HashMap<&'de str, FieldDef<'de>>;
@@ -111,29 +167,85 @@ type RawEntryList<'de> = HashMap<&'de str, RawEntry<'de>>;
 
 
 
+#[derive(Default)]
 pub struct RawBibliography<'de> {
     macros: MacroList<'de>,
-    entries: RawEntryList<'de>,
+    pub(crate) entries: RawEntryList<'de>,
 }
 
 impl<'de> RawBibliography<'de> {
 
     #[inline]
     pub fn new() -> Self {
-        Self {
-            macros: HashMap::new(),
-            entries: HashMap::new(),
-        }
+        Self::default()
     }
 
     #[inline]
     pub fn add_bibtex_resource(&mut self, input: &'de Input) -> Result<(), Error> {
-        Ok(parse::Parser::new(input).parse(self)?)
+        parse::Parser::new(input).parse(self)
+    }
+
+    /// Expands macros and joins compound values.
+    pub(crate) fn resolve_value(&self, value: &RawValue<'de>) -> Result<Text<'de>, Error> {
+        match value {
+            RawValue::Simple(slice) => Ok(Text::parse(slice)),
+            RawValue::Macro(name) => match self.lookup_macro(name.str) {
+                Some(def) => self.resolve_value(&def.value),
+                None => match month_macro(name.str) {
+                    Some(month) => Ok(Text::parse(&month)),
+                    None => Err(Error::UndefinedMacro(name.to_string(), name.trace())),
+                }
+            },
+            RawValue::Compound(parts) => {
+                let mut text = Text::default();
+                for part in parts {
+                    text.append(self.resolve_value(part)?);
+                }
+                Ok(text)
+            }
+        }
+    }
+
+    /// Replaces the macros in the value of `@string` macro `name` by their
+    /// definitions, as BibTeX does. Macros can only use those defined before
+    /// them, so they cannot be recursive.
+    pub(crate) fn expand_definition(&self, name: &InputSlice<'de>, value: RawValue<'de>) -> Result<RawValue<'de>, Error> {
+        match value {
+            RawValue::Simple(_) => Ok(value),
+            RawValue::Macro(other) => match self.lookup_macro(other.str) {
+                Some(def) => Ok(def.value.clone()),
+                None => match month_macro(other.str) {
+                    Some(month) => Ok(RawValue::Simple(month)),
+                    None if other.str.eq_ignore_ascii_case(name.str) => Err(Error::RecursiveMacro(name.to_string(), other.trace())),
+                    None => Err(Error::UndefinedMacro(other.to_string(), other.trace())),
+                },
+            },
+            RawValue::Compound(parts) => parts.into_iter()
+                .map(|part| self.expand_definition(name, part))
+                .collect::<Result<_, _>>()
+                .map(RawValue::Compound),
+        }
+    }
+
+    /// Macro names are case insensitive.
+    fn lookup_macro(&self, name: &str) -> Option<&FieldDef<'de>> {
+        self.macros.get(name).or_else(|| {
+            self.macros.iter().find(|(other, _)| other.eq_ignore_ascii_case(name)).map(|(_, def)| def)
+        })
     }
 
 }
 
 
 
+/// Predefined month macros, `jan` to `dec`, expand to the month number.
+fn month_macro(name: &str) -> Option<InputSlice<'static>> {
+    const NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    const NUMBERS: [&str; 12] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12"];
+    let month = NAMES.iter().position(|month| month.eq_ignore_ascii_case(name))?;
+    Some(InputSlice::generated(NUMBERS[month]))
+}
+
+
 #[cfg(test)]
 mod tests;
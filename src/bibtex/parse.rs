@@ -33,7 +33,7 @@ impl<'de> Parser<'de>{
     
     pub(super) fn new(input: &'de Input) -> Self {
         Parser {
-            input: input,
+            input,
             index: 0,
             saved_index: 0,
         }
@@ -76,11 +76,8 @@ impl<'de> Parser<'de>{
     // -----------------------
 
     fn discard_line(&mut self) {
-        loop {
-            match self.next() {
-                Some(b'\n') => return,
-                _ => (),
-            }
+        while let Some(byte) = self.next() {
+            if byte == b'\n' { return }
         }
     }
 
@@ -115,7 +112,7 @@ impl<'de> Parser<'de>{
     fn saved_until_last(&self) -> InputSlice<'de> {
         InputSlice {
             r#str: &self.input.content[self.saved_index..self.index-1],
-            input: self.input,
+            input: Some(self.input),
             offset: self.saved_index,
         }
     }
@@ -173,17 +170,20 @@ impl<'de> Parser<'de>{
     fn parse_string(&mut self) -> Result<(InputSlice<'de>, Option<u8>), Option<u8>> {
         match self.next() { Some(b'"') => (), other => return Err(other) }
         self.save_index();
+        // quotes only end the string outside of braces, there are no escapes
+        let mut depth = 0usize;
         loop {
             match self.next() {
-                Some(b'"') => return Ok((self.saved_until_last(), self.next_after_whitespace())),
-                Some(b'\\') => self.discard_next(),
+                Some(b'"') if depth == 0 => return Ok((self.saved_until_last(), self.next_after_whitespace())),
+                Some(b'{') => depth += 1,
+                Some(b'}') => depth = depth.saturating_sub(1),
                 Some(_) => (),
                 None => return Err(None),
             }
         }
     }
 
-    fn parse_value(&mut self) -> Result<(RawValue<'de>, Option<u8>), Option<u8>> {
+    fn parse_part(&mut self) -> Result<(RawValue<'de>, Option<u8>), Option<u8>> {
         match self.peek_after_whitespace() {
             Some(b'0'..=b'9') => {
                 let (value, next) = self.parse_number();
@@ -195,24 +195,30 @@ impl<'de> Parser<'de>{
             },
             Some(b'"') => {
                 let (value, next) = self.parse_string()?;
-                if next == Some(b'#') {
-                    todo!("compoound value")
-                } else {
-                    return Ok((RawValue::Simple(value), next));
-                }
+                Ok((RawValue::Simple(value), next))
             },
             Some(_) => {
                 let (name, next) = self.parse_identifier();
-                if next == Some(b'#') {
-                    todo!("compoound value")
-                } else {
-                    return Ok((RawValue::Macro(name), next));
-                }
+                Ok((RawValue::Macro(name), next))
             }
-            None => return Err(None),
+            None => Err(None),
         }
     }
 
+    fn parse_value(&mut self) -> Result<(RawValue<'de>, Option<u8>), Option<u8>> {
+        let (value, mut next) = self.parse_part()?;
+        if next != Some(b'#') {
+            return Ok((value, next))
+        }
+        let mut parts = vec![value];
+        while next == Some(b'#') {
+            let part;
+            (part, next) = self.parse_part()?;
+            parts.push(part);
+        }
+        Ok((RawValue::Compound(parts), next))
+    }
+
     fn close_brace(&mut self) -> Result<(), Option<u8>>{
         let mut depth = 1;
         loop {
@@ -282,11 +288,8 @@ impl<'de> Parser<'de>{
                     } 
 
                     let (value, next) = self.parse_value().map_err(map_err!(TokenContext::MacroDef(entrytype.trace()), self.trace_last()))?;
-                    if let RawValue::Simple(_) = value {
-                        bib.macros.insert(name.str, FieldDef{name, value});
-                    } else {
-                        return Err(Error::RecursiveMacro(name.to_string(), name.trace()));
-                    }
+                    let value = bib.expand_definition(&name, value)?;
+                    bib.macros.insert(name.str, FieldDef{name, value});
                     
                     match next {
                         Some(b',') => continue,
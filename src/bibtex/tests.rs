@@ -108,6 +108,63 @@ fn macro_use() {
     ).unwrap();
 }
 
+#[test]
+fn compound_values() {
+    let input = super::Input::new("<internal test>", concat!(
+        "@string{a = \"x\" # \"y\", b = a # { z} # 12}\n",
+        "@string{c = B # \" \" # feb}\n",
+        "@entry{key, field = c # \"!\" # mar}",
+    ));
+    let mut bib = super::RawBibliography::new();
+    bib.add_bibtex_resource(&input).unwrap();
+    let field = &bib.entries["key"].fields["field"];
+    assert_eq!(bib.resolve_value(&field.value).unwrap().to_string(), "xy z12 2!3");
+}
+
+#[test]
+fn macro_definition_errors() {
+    let input = super::Input::new("<internal test>", "@string{a = \"x\" # a}");
+    match super::RawBibliography::new().add_bibtex_resource(&input) {
+        Err(super::Error::RecursiveMacro(name, trace)) => assert_eq!((name.as_str(), trace.col), ("a", 19)),
+        other => panic!("unexpected {:?}", other),
+    }
+    let input = super::Input::new("<internal test>", "@string{a = \"x\" # b}");
+    match super::RawBibliography::new().add_bibtex_resource(&input) {
+        Err(super::Error::UndefinedMacro(name, _)) => assert_eq!(name, "b"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn non_ascii_traces() {
+    let input = super::Input::new("<internal test>", "Ünicöde\n  öx");
+    let slice = input.slice();
+    let trace = |offset: usize| {
+        let trace = slice.slice(offset..slice.as_str().len()).trace();
+        (trace.line, trace.col)
+    };
+    assert_eq!(trace(0), (1, 1));
+    assert_eq!(trace(5), (1, 5));
+    assert_eq!(trace(9), (1, 8));
+    assert_eq!(trace(12), (2, 3));
+    let text = crate::latex::Text::parse(&slice);
+    assert_eq!(text.segments()[0].span().trace().col, 1);
+
+    let input = super::Input::new("<internal test>", "@book{k, ü = {a}, ü = {b}}");
+    match super::RawBibliography::new().add_bibtex_resource(&input) {
+        Err(super::Error::DoubleField(name, first, second)) => assert_eq!((name.as_str(), first.col, second.col), ("ü", 10, 19)),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn comment_at_end_of_input() {
+    let input = super::Input::new("<internal test>", "@entry{key}\n% no line break");
+    let mut bib = super::RawBibliography::new();
+    bib.add_bibtex_resource(&input).unwrap();
+    assert_eq!(bib.entries.len(), 1);
+}
+
 
 
 
@@ -136,4 +193,36 @@ fn biber_benchmark() {
 
     assert_eq!(bib.macros.len(), 635);
     assert_eq!(bib.entries.len(), 2157);
-}
\ No newline at end of file
+}
+
+#[test]
+fn biber_benchmark_latex_roundtrip() {
+    use crate::latex::{decode, encode, Charset, DecodeOptions};
+
+    fn text<'de>(bib: &super::RawBibliography<'de>, value: &super::RawValue<'de>) -> String {
+        match value {
+            super::RawValue::Simple(slice) => slice.to_string(),
+            super::RawValue::Macro(name) => bib.macros.get(name.str).map(|def| text(bib, &def.value)).unwrap_or_default(),
+            super::RawValue::Compound(parts) => parts.iter().map(|part| text(bib, part)).collect(),
+        }
+    }
+
+    let inputs = vec![
+        super::Input::from_file(test_file("biber-benchmark-definitions.bib")).unwrap(),
+        super::Input::from_file(test_file("biber-benchmark-papers.bib")).unwrap(),
+    ];
+    let mut bib = super::RawBibliography::new();
+    for input in &inputs {
+        bib.add_bibtex_resource(input).unwrap();
+    }
+
+    let options = DecodeOptions::default();
+    for entry in bib.entries.values() {
+        for field in entry.fields.values() {
+            let decoded = decode(&text(&bib, &field.value), &options);
+            for charset in [Charset::Base, Charset::Full] {
+                assert_eq!(decode(&encode(&decoded, charset), &options), decoded);
+            }
+        }
+    }
+}
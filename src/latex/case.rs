@@ -0,0 +1,233 @@
+use super::symbols;
+use super::text::{Command, Segment, Text};
+
+
+#[derive(Debug, Clone)]
+pub struct CaseOptions {
+    /// Languages whose titles are case changed, as biblatex's `\DeclareCaseLangs`.
+    pub languages: Vec<String>,
+    /// Words kept lowercase in title case unless they start the title or a subtitle.
+    pub small_words: Vec<String>,
+}
+
+impl Default for CaseOptions {
+    fn default() -> Self {
+        let languages = [
+            "american", "british", "canadian", "english", "australian",
+            "newzealand", "USenglish", "UKenglish",
+        ];
+        let small_words = [
+            "a", "an", "the", "and", "but", "or", "nor", "for", "so", "yet",
+            "as", "at", "by", "in", "of", "off", "on", "per", "to", "up",
+            "via", "vs", "from", "into", "with", "over",
+        ];
+        CaseOptions {
+            languages: languages.iter().map(|lang| lang.to_string()).collect(),
+            small_words: small_words.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+}
+
+impl CaseOptions {
+
+    /// Entries without `langid` are assumed to use a case changing language.
+    fn applies_to(&self, language: Option<&str>) -> bool {
+        language.is_none_or(|language| self.languages.iter().any(|lang| lang.eq_ignore_ascii_case(language)))
+    }
+}
+
+
+/// Converts to sentence case like biblatex's `\MakeSentenceCase*`: the first
+/// word and words after a colon are capitalised, all others lowercased.
+/// Protected groups, math, commands and acronyms are left untouched.
+pub fn sentence_case(text: &Text, language: Option<&str>, options: &CaseOptions) -> String {
+    if !options.applies_to(language) {
+        return text.to_string();
+    }
+    let mut caser = Caser::new(Mode::Sentence, options);
+    caser.text(text);
+    caser.out
+}
+
+/// Converts to title case: all words except small words are capitalised.
+pub fn title_case(text: &Text, language: Option<&str>, options: &CaseOptions) -> String {
+    if !options.applies_to(language) {
+        return text.to_string();
+    }
+    let mut caser = Caser::new(Mode::Title, options);
+    caser.text(text);
+    caser.out
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Sentence,
+    Title,
+}
+
+struct Caser<'a> {
+    mode: Mode,
+    options: &'a CaseOptions,
+    out: String,
+    // the next word starts the title or a subtitle
+    capitalize: bool,
+    // the last output was part of a word
+    in_word: bool,
+}
+
+impl<'a> Caser<'a> {
+
+    fn new(mode: Mode, options: &'a CaseOptions) -> Self {
+        Caser { mode, options, out: String::new(), capitalize: true, in_word: false }
+    }
+
+    fn text(&mut self, text: &Text) {
+        for segment in text.segments() {
+            self.segment(segment);
+        }
+    }
+
+    fn segment(&mut self, segment: &Segment) {
+        match segment {
+            Segment::Plain(span) => self.plain(span.as_str()),
+            Segment::Group(group) if !group.is_protected() => {
+                self.out.push('{');
+                self.text(&group.content);
+                self.out.push('}');
+            },
+            Segment::Command(command) if !command.is_protected() => self.command(command),
+            _ => {
+                self.out.push_str(segment.span().as_str());
+                self.capitalize = false;
+                self.in_word = true;
+            },
+        }
+    }
+
+    fn command(&mut self, command: &Command) {
+        let source = command.span.as_str();
+        let start = command.span.offset();
+        let mut cursor = 0;
+        if command.args.is_empty() {
+            let name = command.name.as_str();
+            let changed = match self.word_case() {
+                Some(true) => special_case(name, char::to_uppercase),
+                Some(false) => special_case(name, char::to_lowercase),
+                None => None,
+            };
+            if let Some(changed) = changed {
+                let name_start = command.name.offset() - start;
+                self.out.push_str(&source[..name_start]);
+                self.out.push_str(changed);
+                cursor = name_start + name.len();
+            }
+            if symbols::lookup(symbols::SPECIALS, name).is_some_and(|symbol| symbol.chars().any(char::is_alphabetic)) {
+                self.letter();
+            }
+        }
+        for arg in &command.args {
+            for segment in arg.segments() {
+                let offset = segment.span().offset() - start;
+                self.out.push_str(&source[cursor..offset]);
+                self.segment(segment);
+                cursor = offset + segment.span().as_str().len();
+            }
+        }
+        self.out.push_str(&source[cursor..]);
+    }
+
+    /// Case of a single letter at the current position, `None` to keep it.
+    fn word_case(&self) -> Option<bool> {
+        match (self.mode, self.in_word) {
+            (Mode::Sentence, false) => Some(self.capitalize),
+            (Mode::Sentence, true) => Some(false),
+            (Mode::Title, false) => Some(true),
+            (Mode::Title, true) => None,
+        }
+    }
+
+    fn letter(&mut self) {
+        self.capitalize = false;
+        self.in_word = true;
+    }
+
+    fn plain(&mut self, text: &str) {
+        let mut rest = text;
+        while let Some(ch) = rest.chars().next() {
+            let len = word_len(rest);
+            if len == 0 {
+                match ch {
+                    ':' | '?' | '!' => self.capitalize = true,
+                    _ => (),
+                }
+                self.in_word = false;
+                self.out.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            } else {
+                self.word(&rest[..len]);
+                rest = &rest[len..];
+            }
+        }
+    }
+
+    fn word(&mut self, word: &str) {
+        let mut chars = word.chars();
+        let first = chars.next().unwrap_or_default();
+        // acronyms and words like `iPhone` or `LaTeX` keep their case
+        let acronym = chars.any(char::is_uppercase) || (self.in_word && first.is_uppercase());
+        let small = self.options.small_words.iter().any(|small| small.eq_ignore_ascii_case(word));
+        if acronym {
+            self.out.push_str(word);
+        } else {
+            match (self.mode, self.in_word) {
+                (Mode::Sentence, false) if self.capitalize => push_capitalized(&mut self.out, word, true),
+                (Mode::Sentence, _) => self.out.push_str(&word.to_lowercase()),
+                (Mode::Title, false) if self.capitalize || !small => push_capitalized(&mut self.out, word, false),
+                (Mode::Title, false) => self.out.push_str(&word.to_lowercase()),
+                (Mode::Title, true) => self.out.push_str(word),
+            }
+        }
+        self.letter();
+    }
+}
+
+
+/// Length of the word at the start of `text`, including inner apostrophes.
+fn word_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((pos, ch)) = chars.next() {
+        let inner_apostrophe = matches!(ch, '\'' | '’') && len > 0
+            && chars.peek().is_some_and(|(_, next)| next.is_alphanumeric());
+        if !(ch.is_alphanumeric() || inner_apostrophe) {
+            break;
+        }
+        len = pos + ch.len_utf8();
+    }
+    len
+}
+
+fn push_capitalized(out: &mut String, word: &str, lower_rest: bool) {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+        out.extend(first.to_uppercase());
+        if lower_rest {
+            out.push_str(&chars.as_str().to_lowercase());
+        } else {
+            out.push_str(chars.as_str());
+        }
+    }
+}
+
+/// Name of the special character command with the other case, e.g. `\O` for `\o`.
+fn special_case<I: Iterator<Item = char>>(name: &str, change: fn(char) -> I) -> Option<&'static str> {
+    let symbol = symbols::lookup(symbols::SPECIALS, name)?;
+    let mut chars = symbol.chars();
+    let (Some(ch), None) = (chars.next(), chars.next()) else { return None };
+    let mut changed = change(ch);
+    let (Some(target), None) = (changed.next(), changed.next()) else { return None };
+    symbols::SPECIALS.iter()
+        .find(|(_, other)| other.chars().eq(std::iter::once(target)))
+        .map(|(name, _)| *name)
+}
@@ -0,0 +1,316 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use super::{find_unescaped, starts_ligature};
+use super::symbols::{self, lookup};
+
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Normalization {
+    #[default]
+    Nfc,
+    Nfd,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    pub normalization: Normalization,
+    /// Keep commands without a Unicode equivalent (like biber does). If
+    /// `false`, they are dropped and only their arguments remain.
+    pub keep_unknown: bool,
+    /// Drop braces that are not needed for a kept command.
+    pub strip_braces: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            normalization: Normalization::default(),
+            keep_unknown: true,
+            strip_braces: false,
+        }
+    }
+}
+
+
+/// Converts LaTeX markup to Unicode, e.g. `{\"U}nic{\"o}de` to `Ünicöde`.
+pub fn decode(text: &str, options: &DecodeOptions) -> String {
+    let mut decoder = Decoder::new(text, options);
+    let mut out = String::with_capacity(text.len());
+    decoder.decode_text(&mut out, false);
+    match options.normalization {
+        Normalization::Nfc => out.nfc().collect(),
+        Normalization::Nfd => out.nfd().collect(),
+    }
+}
+
+
+struct Decoder<'a> {
+    text: &'a str,
+    pos: usize,
+    options: &'a DecodeOptions,
+    // an unknown command was kept in math mode
+    unknown_math: bool,
+}
+
+impl<'a> Decoder<'a> {
+
+    fn new(text: &'a str, options: &'a DecodeOptions) -> Self {
+        Decoder { text, pos: 0, options, unknown_math: false }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.peek().filter(|ch| ch.is_whitespace()) {
+            self.pos += ch.len_utf8();
+        }
+    }
+
+    /// Reads the name of the command starting at the current backslash.
+    fn command_name(&mut self, skip_spaces: bool) -> Option<&'a str> {
+        self.pos += 1;
+        let rest = &self.text[self.pos..];
+        let len = match rest.chars().next() {
+            Some(ch) if ch.is_ascii_alphabetic() => {
+                rest.find(|ch: char| !ch.is_ascii_alphabetic()).unwrap_or(rest.len())
+            },
+            Some(ch) => ch.len_utf8(),
+            None => return None,
+        };
+        self.pos += len;
+        if skip_spaces && rest.as_bytes()[0].is_ascii_alphabetic() {
+            self.skip_whitespace();
+        }
+        Some(&rest[..len])
+    }
+
+
+    // Text mode
+    // ---------
+
+    /// Decodes until the end of the input or, if `nested`, the closing brace.
+    fn decode_text(&mut self, out: &mut String, nested: bool) {
+        // the following group belongs to a kept command
+        let mut argument = false;
+        while let Some(ch) = self.peek() {
+            match ch {
+                '}' if nested => {
+                    self.pos += 1;
+                    return;
+                },
+                '{' if self.breaks_ligature(out) => self.pos += 2,
+                '{' => {
+                    self.pos += 1;
+                    self.group(out, argument);
+                },
+                '\\' => {
+                    argument = self.command(out);
+                    continue;
+                },
+                '$' => self.math(out),
+                _ => match symbols::LIGATURES.iter().find(|(lig, _)| self.text[self.pos..].starts_with(lig)) {
+                    Some((lig, replacement)) => {
+                        self.pos += lig.len();
+                        out.push_str(replacement);
+                    },
+                    None => {
+                        self.pos += ch.len_utf8();
+                        out.push(ch);
+                    },
+                },
+            }
+            argument = false;
+        }
+    }
+
+    fn group(&mut self, out: &mut String, argument: bool) {
+        // `{\"o}` and `{\ss}` are special characters in BibTeX
+        let special = self.peek() == Some('\\');
+        let mut inner = String::new();
+        self.decode_text(&mut inner, true);
+        let resolved = special && !inner.contains('\\');
+        if argument || !(resolved || self.options.strip_braces) {
+            out.push('{');
+            out.push_str(&inner);
+            out.push('}');
+        } else {
+            out.push_str(&inner);
+        }
+    }
+
+    /// Returns whether an unknown command was kept.
+    fn command(&mut self, out: &mut String) -> bool {
+        let start = self.pos;
+        let name = match self.command_name(true) {
+            Some(name) => name,
+            None => {
+                out.push('\\');
+                return false;
+            },
+        };
+        if let Some(accent) = lookup(symbols::ACCENTS, name) {
+            self.accent(out, accent);
+            false
+        } else if let Some(special) = lookup(symbols::SPECIALS, name) {
+            out.push_str(special);
+            self.skip_empty_group();
+            false
+        } else if self.options.keep_unknown {
+            out.push_str(&self.text[start..self.pos]);
+            true
+        } else {
+            self.skip_empty_group();
+            false
+        }
+    }
+
+    /// Whether an empty group at the current position only separates the
+    /// characters of a ligature, as in `-{}-`.
+    fn breaks_ligature(&self, out: &str) -> bool {
+        let Some(rest) = self.text[self.pos..].strip_prefix("{}") else { return false };
+        match (out.chars().last(), rest.chars().next()) {
+            (Some(first), Some(second)) => starts_ligature(first, second),
+            _ => false,
+        }
+    }
+
+    /// The `{}` in `x\ss{}y` only ends the command name.
+    fn skip_empty_group(&mut self) {
+        if self.text[self.pos..].starts_with("{}") {
+            self.pos += 2;
+        }
+    }
+
+    fn accent(&mut self, out: &mut String, accent: char) {
+        self.skip_whitespace();
+        let mut base = String::new();
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.decode_text(&mut base, true);
+            },
+            Some('\\') => {
+                self.command(&mut base);
+            },
+            Some(ch) => {
+                self.pos += ch.len_utf8();
+                base.push(ch);
+            },
+            None => (),
+        }
+        let mut chars = base.chars();
+        match chars.next() {
+            // dotless i and j are only needed to place accents in LaTeX
+            Some('ı') => out.push('i'),
+            Some('ȷ') => out.push('j'),
+            Some(first) => out.push(first),
+            None => (),
+        }
+        // keep accents of nested commands in order, e.g. `\'{\"u}`
+        let rest = chars.as_str();
+        let marks = rest.find(|ch| !is_combining_mark(ch)).unwrap_or(rest.len());
+        out.push_str(&rest[..marks]);
+        out.push(accent);
+        out.push_str(&rest[marks..]);
+    }
+
+
+    // Math mode
+    // ---------
+
+    fn math(&mut self, out: &mut String) {
+        let start = self.pos;
+        let delimiter = if self.text[start..].starts_with("$$") { "$$" } else { "$" };
+        let content_start = start + delimiter.len();
+        let content_end = match find_unescaped(&self.text[content_start..], delimiter) {
+            Some(len) => content_start + len,
+            None => {
+                out.push_str(&self.text[start..]);
+                self.pos = self.text.len();
+                return;
+            },
+        };
+        self.pos = content_end + delimiter.len();
+
+        let mut decoder = Decoder::new(&self.text[content_start..content_end], self.options);
+        let mut inner = String::new();
+        decoder.decode_math(&mut inner, false);
+        if decoder.unknown_math {
+            out.push_str(&self.text[start..self.pos]);
+        } else {
+            out.push_str(&inner);
+        }
+    }
+
+    fn decode_math(&mut self, out: &mut String, nested: bool) {
+        while let Some(ch) = self.peek() {
+            match ch {
+                '}' if nested => {
+                    self.pos += 1;
+                    return;
+                },
+                '{' => {
+                    self.pos += 1;
+                    self.decode_math(out, true);
+                },
+                '^' => self.script(out, ch, symbols::SUPERSCRIPTS),
+                '_' => self.script(out, ch, symbols::SUBSCRIPTS),
+                '\\' => self.math_command(out),
+                _ => {
+                    self.pos += ch.len_utf8();
+                    out.push(ch);
+                },
+            }
+        }
+    }
+
+    fn math_command(&mut self, out: &mut String) {
+        let name = match self.command_name(false) {
+            Some(name) => name,
+            None => return,
+        };
+        if let Some(symbol) = lookup(symbols::MATH, name) {
+            out.push_str(symbol);
+        } else if symbols::MATH_FONTS.contains(&name) {
+            self.skip_whitespace();
+            if self.peek() == Some('{') {
+                self.pos += 1;
+                self.decode_math(out, true);
+            }
+        } else if self.options.keep_unknown {
+            self.unknown_math = true;
+        }
+    }
+
+    fn script(&mut self, out: &mut String, marker: char, table: &[(char, char)]) {
+        self.pos += 1;
+        self.skip_whitespace();
+        let mut argument = String::new();
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.decode_math(&mut argument, true);
+            },
+            Some('\\') => self.math_command(&mut argument),
+            Some(ch) => {
+                self.pos += ch.len_utf8();
+                argument.push(ch);
+            },
+            None => (),
+        }
+        let scripted: Option<String> = argument.chars()
+            .map(|ch| table.iter().find(|(plain, _)| *plain == ch).map(|(_, script)| *script))
+            .collect();
+        match scripted {
+            Some(scripted) if !scripted.is_empty() => out.push_str(&scripted),
+            _ => {
+                out.push(marker);
+                out.push_str(&argument);
+            },
+        }
+    }
+}
+
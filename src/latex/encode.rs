@@ -0,0 +1,146 @@
+use unicode_normalization::UnicodeNormalization;
+
+use super::{starts_ligature, symbols};
+
+
+/// Character sets of biber's `--output-safecharsset`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// Accented and special letters only.
+    #[default]
+    Base,
+    /// Additionally punctuation and math symbols.
+    Full,
+}
+
+
+/// Converts non-ASCII characters to LaTeX commands (biber's `--output-safechars`).
+/// Existing markup like `{\"U}` or `$...$` is left untouched. With
+/// [`Charset::Full`], ASCII that LaTeX would turn into other characters is
+/// escaped too, e.g. `~` and the ligature `--`.
+pub fn encode(text: &str, charset: Charset) -> String {
+    let text: String = text.nfc().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut math = false;
+    let mut escaped = false;
+    // the previous character, if it was plain text
+    let mut previous = None;
+    for ch in text.chars() {
+        if ch.is_ascii() {
+            let plain = !math && !escaped && ch != '\\';
+            if ch == '$' && !escaped {
+                math = !math;
+            }
+            escaped = ch == '\\' && !escaped;
+            if plain && charset == Charset::Full {
+                if ch == '~' {
+                    out.push_str("\\textasciitilde{}");
+                    previous = None;
+                    continue;
+                }
+                if previous.is_some_and(|previous| starts_ligature(previous, ch)) {
+                    out.push_str("{}");
+                }
+            }
+            previous = plain.then_some(ch);
+            out.push(ch);
+            continue;
+        }
+        escaped = false;
+        previous = None;
+        let encoded = if math {
+            math_symbol(ch).map(|name| format!("\\{}{{}}", name))
+        } else {
+            encode_char(ch, charset)
+        };
+        match encoded {
+            Some(encoded) => out.push_str(&encoded),
+            None => out.push(ch),
+        }
+    }
+    out
+}
+
+
+fn encode_char(ch: char, charset: Charset) -> Option<String> {
+    if let Some(name) = special(ch) {
+        if charset == Charset::Full || ch.is_alphabetic() {
+            return Some(format!("{{\\{}}}", name));
+        }
+    }
+    if let Some(accented) = accented(ch) {
+        return Some(accented);
+    }
+    if charset == Charset::Base {
+        return None;
+    }
+    if let Some((ligature, _)) = symbols::LIGATURES.iter().find(|(_, symbol)| single(symbol) == Some(ch)) {
+        return Some((*ligature).to_owned());
+    }
+    if let Some(name) = math_symbol(ch) {
+        return Some(format!("$\\{}$", name));
+    }
+    if let Some((plain, _)) = symbols::SUPERSCRIPTS.iter().find(|(_, script)| *script == ch) {
+        return Some(format!("$^{{{}}}$", plain));
+    }
+    if let Some((plain, _)) = symbols::SUBSCRIPTS.iter().find(|(_, script)| *script == ch) {
+        return Some(format!("$_{{{}}}$", plain));
+    }
+    None
+}
+
+fn single(symbol: &str) -> Option<char> {
+    let mut chars = symbol.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) if !ch.is_ascii() => Some(ch),
+        _ => None,
+    }
+}
+
+fn special(ch: char) -> Option<&'static str> {
+    symbols::SPECIALS.iter()
+        .find(|(_, symbol)| single(symbol) == Some(ch))
+        .map(|(name, _)| *name)
+}
+
+fn math_symbol(ch: char) -> Option<&'static str> {
+    symbols::MATH.iter()
+        .find(|(_, symbol)| single(symbol) == Some(ch))
+        .map(|(name, _)| *name)
+}
+
+fn accent(mark: char) -> Option<&'static str> {
+    symbols::ACCENTS.iter()
+        .find(|(_, combining)| *combining == mark)
+        .map(|(name, _)| *name)
+}
+
+/// Letters composed of a base letter and supported accents, e.g. `ǘ` to `{\'{\"u}}`.
+fn accented(ch: char) -> Option<String> {
+    let mut chars = ch.to_string().nfd().collect::<Vec<_>>().into_iter();
+    let base = chars.next()?;
+    let marks: Vec<char> = chars.collect();
+    if marks.is_empty() {
+        return None;
+    }
+    let mut inner = match base {
+        // dotless i and j below accents above the letter
+        'i' | 'j' if !matches!(marks[0], '\u{0323}' | '\u{0326}' | '\u{0327}' | '\u{0328}' | '\u{0331}') => {
+            format!("\\{}", base)
+        },
+        _ if base.is_ascii_alphabetic() => base.to_string(),
+        _ => format!("\\{}", special(base).filter(|_| base.is_alphabetic())?),
+    };
+    for mark in marks {
+        let name = accent(mark)?;
+        let letter = name.starts_with(|ch: char| ch.is_ascii_alphabetic());
+        let bare = inner.chars().count() == 1
+            || inner.strip_prefix('\\').is_some_and(|cmd| cmd.chars().all(|ch| ch.is_ascii_alphabetic()));
+        inner = if bare && !letter {
+            format!("\\{}{}", name, inner)
+        } else {
+            format!("\\{}{{{}}}", name, inner)
+        };
+    }
+    Some(format!("{{{}}}", inner))
+}
@@ -0,0 +1,39 @@
+mod case;
+mod decode;
+mod encode;
+mod symbols;
+mod text;
+
+pub use case::{sentence_case, title_case, CaseOptions};
+pub use decode::{decode, DecodeOptions, Normalization};
+pub use encode::{encode, Charset};
+pub use text::{Command, Group, Segment, Text};
+
+
+/// Byte offset of the first `pattern` that is not escaped by a backslash.
+fn find_unescaped(text: &str, pattern: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (pos, ch) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if text[pos..].starts_with(pattern) {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+/// Whether `first` followed by `second` starts a ligature like `--`, which
+/// `-{}-` breaks.
+fn starts_ligature(first: char, second: char) -> bool {
+    symbols::LIGATURES.iter().any(|(ligature, _)| {
+        let mut chars = ligature.chars();
+        chars.next() == Some(first) && chars.next() == Some(second)
+    })
+}
+
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,305 @@
+// Symbol tables shared by decoding and encoding. The first entry for a
+// character is the preferred spelling when encoding.
+
+
+/// Accent commands and their combining characters.
+pub(super) const ACCENTS: &[(&str, char)] = &[
+    ("`", '\u{0300}'),
+    ("'", '\u{0301}'),
+    ("^", '\u{0302}'),
+    ("~", '\u{0303}'),
+    ("=", '\u{0304}'),
+    ("u", '\u{0306}'),
+    (".", '\u{0307}'),
+    ("\"", '\u{0308}'),
+    ("r", '\u{030A}'),
+    ("H", '\u{030B}'),
+    ("v", '\u{030C}'),
+    ("textcommabelow", '\u{0326}'),
+    ("d", '\u{0323}'),
+    ("c", '\u{0327}'),
+    ("k", '\u{0328}'),
+    ("b", '\u{0331}'),
+    ("t", '\u{0361}'),
+];
+
+/// Text mode commands without arguments.
+pub(super) const SPECIALS: &[(&str, &str)] = &[
+    ("ss", "ß"),
+    ("SS", "ẞ"),
+    ("o", "ø"),
+    ("O", "Ø"),
+    ("ae", "æ"),
+    ("AE", "Æ"),
+    ("oe", "œ"),
+    ("OE", "Œ"),
+    ("aa", "å"),
+    ("AA", "Å"),
+    ("l", "ł"),
+    ("L", "Ł"),
+    ("i", "ı"),
+    ("j", "ȷ"),
+    ("dh", "ð"),
+    ("DH", "Ð"),
+    ("dj", "đ"),
+    ("DJ", "Đ"),
+    ("th", "þ"),
+    ("TH", "Þ"),
+    ("ng", "ŋ"),
+    ("NG", "Ŋ"),
+    ("&", "&"),
+    ("%", "%"),
+    ("$", "$"),
+    ("#", "#"),
+    ("_", "_"),
+    ("{", "{"),
+    ("}", "}"),
+    (" ", " "),
+    (",", "\u{2009}"),
+    ("textendash", "–"),
+    ("textemdash", "—"),
+    ("textquoteleft", "‘"),
+    ("textquoteright", "’"),
+    ("textquotedblleft", "“"),
+    ("textquotedblright", "”"),
+    ("guillemotleft", "«"),
+    ("guillemotright", "»"),
+    ("guilsinglleft", "‹"),
+    ("guilsinglright", "›"),
+    ("textexclamdown", "¡"),
+    ("textquestiondown", "¿"),
+    ("textbackslash", "\\"),
+    ("textasciitilde", "~"),
+    ("textasciicircum", "^"),
+    ("textbar", "|"),
+    ("textless", "<"),
+    ("textgreater", ">"),
+    ("textbullet", "•"),
+    ("textperiodcentered", "·"),
+    ("textdegree", "°"),
+    ("textregistered", "®"),
+    ("texttrademark", "™"),
+    ("textcopyright", "©"),
+    ("copyright", "©"),
+    ("textsection", "§"),
+    ("S", "§"),
+    ("textparagraph", "¶"),
+    ("P", "¶"),
+    ("textdagger", "†"),
+    ("dag", "†"),
+    ("textdaggerdbl", "‡"),
+    ("ddag", "‡"),
+    ("textellipsis", "…"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("pounds", "£"),
+    ("textsterling", "£"),
+    ("texteuro", "€"),
+    ("euro", "€"),
+    ("textyen", "¥"),
+    ("textcent", "¢"),
+    ("textonehalf", "½"),
+    ("textonequarter", "¼"),
+    ("textthreequarters", "¾"),
+    ("textordfeminine", "ª"),
+    ("textordmasculine", "º"),
+    ("textmu", "µ"),
+    ("textperthousand", "‰"),
+    ("textnumero", "№"),
+    ("LaTeX", "LaTeX"),
+    ("TeX", "TeX"),
+    // invisible
+    ("/", ""),
+    ("@", ""),
+    ("-", ""),
+    ("relax", ""),
+];
+
+/// Text mode commands whose argument is regular text.
+pub(super) const FORMATTING: &[&str] = &[
+    "emph", "textbf", "textit", "textsl", "textup", "textmd", "textrm",
+    "textsf", "texttt", "textnormal", "textsuperscript", "textsubscript",
+    "mkbibemph", "mkbibitalic", "mkbibbold", "mkbibquote", "enquote",
+];
+
+/// Ligatures of plain text, longest first.
+pub(super) const LIGATURES: &[(&str, &str)] = &[
+    ("---", "—"),
+    ("--", "–"),
+    ("``", "“"),
+    ("''", "”"),
+    ("?`", "¿"),
+    ("!`", "¡"),
+    ("~", "\u{00A0}"),
+];
+
+/// Math mode commands without arguments.
+pub(super) const MATH: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("varrho", "ϱ"),
+    ("sigma", "σ"),
+    ("varsigma", "ς"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("aleph", "ℵ"),
+    ("ell", "ℓ"),
+    ("infty", "∞"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("emptyset", "∅"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("ni", "∋"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("wedge", "∧"),
+    ("land", "∧"),
+    ("vee", "∨"),
+    ("lor", "∨"),
+    ("oplus", "⊕"),
+    ("otimes", "⊗"),
+    ("times", "×"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("cdot", "⋅"),
+    ("circ", "∘"),
+    ("ast", "∗"),
+    ("star", "⋆"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("sqrt", "√"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("approx", "≈"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("cong", "≅"),
+    ("equiv", "≡"),
+    ("propto", "∝"),
+    ("prec", "≺"),
+    ("succ", "≻"),
+    ("preceq", "⪯"),
+    ("succeq", "⪰"),
+    ("mid", "∣"),
+    ("parallel", "∥"),
+    ("perp", "⊥"),
+    ("models", "⊨"),
+    ("vdash", "⊢"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("gets", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("mapsto", "↦"),
+    ("uparrow", "↑"),
+    ("downarrow", "↓"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("ldots", "…"),
+    ("cdots", "⋯"),
+    ("prime", "′"),
+    ("log", "log"),
+    ("ln", "ln"),
+    ("exp", "exp"),
+    ("min", "min"),
+    ("max", "max"),
+    ("sin", "sin"),
+    ("cos", "cos"),
+    ("lim", "lim"),
+    ("&", "&"),
+    ("%", "%"),
+    ("$", "$"),
+    ("#", "#"),
+    ("_", "_"),
+    ("{", "{"),
+    ("}", "}"),
+    (",", "\u{2009}"),
+    (";", "\u{2005}"),
+    (" ", " "),
+    ("!", ""),
+    ("left", ""),
+    ("right", ""),
+];
+
+/// Math mode commands whose argument is kept as plain text.
+pub(super) const MATH_FONTS: &[&str] = &[
+    "mathrm", "mathit", "mathbf", "mathsf", "mathtt", "mathcal", "mathbb",
+    "mathfrak", "mathnormal", "text", "textrm", "textit", "textbf", "mbox",
+    "operatorname",
+];
+
+pub(super) const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'), ('1', '¹'), ('2', '²'), ('3', '³'), ('4', '⁴'),
+    ('5', '⁵'), ('6', '⁶'), ('7', '⁷'), ('8', '⁸'), ('9', '⁹'),
+    ('+', '⁺'), ('-', '⁻'), ('=', '⁼'), ('(', '⁽'), (')', '⁾'),
+    ('n', 'ⁿ'), ('i', 'ⁱ'),
+];
+
+pub(super) const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'), ('1', '₁'), ('2', '₂'), ('3', '₃'), ('4', '₄'),
+    ('5', '₅'), ('6', '₆'), ('7', '₇'), ('8', '₈'), ('9', '₉'),
+    ('+', '₊'), ('-', '₋'), ('=', '₌'), ('(', '₍'), (')', '₎'),
+];
+
+
+pub(super) fn lookup<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table.iter().find(|(key, _)| *key == name).map(|(_, value)| *value)
+}
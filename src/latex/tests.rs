@@ -0,0 +1,149 @@
+use super::*;
+
+
+fn decode_default(text: &str) -> String {
+    decode(text, &DecodeOptions::default())
+}
+
+// --------------------------
+
+#[test]
+fn decode_accents() {
+    assert_eq!(decode_default("{\\\"U}nic{\\\"o}de"), "Ünicöde");
+    assert_eq!(decode_default("\\\"Unic\\\"{o}de"), "Ünicöde");
+    assert_eq!(decode_default("{\\\"{U}}nic\\\" ode"), "Ünicöde");
+    assert_eq!(decode_default("Erd\\H{o}s and Ko\\v{c}ka"), "Erdős and Kočka");
+    assert_eq!(decode_default("Fran\\c cois and \\c{C}a\\u{g}lar"), "François and Çağlar");
+    assert_eq!(decode_default("Pe{\\~n}a, G{\\'\\i}rard, \\'{\\i}"), "Peña, Gírard, í");
+    assert_eq!(decode_default("\\t{oo}"), "o\u{0361}o");
+}
+
+#[test]
+fn decode_specials() {
+    assert_eq!(decode_default("Stra{\\ss}e, {\\o}re, \\ae{}sop, {\\L}ukasiewicz"), "Straße, øre, æsop, Łukasiewicz");
+    assert_eq!(decode_default("x\\ss{}y, \\ss{}, \\LaTeX{} is"), "xßy, ß, LaTeX is");
+    assert_eq!(decode_default("\\emph{}, {}"), "\\emph{}, {}");
+    assert_eq!(decode_default("Computers \\& Operations Research"), "Computers & Operations Research");
+    assert_eq!(decode_default("pages 1--3 --- or ``more''"), "pages 1–3 — or “more”");
+}
+
+#[test]
+fn decode_math() {
+    assert_eq!(decode_default("The $\\alpha$-$\\beta$ problem in $O(n^2 \\log n)$"), "The α-β problem in O(n² log n)");
+    assert_eq!(decode_default("{P($\\#$P)} and {$W[P]$}"), "{P(#P)} and {W[P]}");
+    assert_eq!(decode_default("$x \\sqsubseteq y$"), "$x \\sqsubseteq y$");
+}
+
+#[test]
+fn decode_unknown_commands() {
+    let title = "The \\emph{{CALMA}} project \\cite{x}";
+    assert_eq!(decode_default(title), title);
+
+    let options = DecodeOptions { keep_unknown: false, strip_braces: true, ..Default::default() };
+    assert_eq!(decode(title, &options), "The CALMA project x");
+    assert_eq!(decode("$x \\sqsubseteq y$", &options), "x  y");
+}
+
+#[test]
+fn decode_normalization() {
+    let options = DecodeOptions { normalization: Normalization::Nfd, ..Default::default() };
+    assert_eq!(decode("\\\"U", &options), "U\u{0308}");
+    assert_eq!(decode_default("\\\"U"), "\u{00DC}");
+}
+
+#[test]
+fn encode_base() {
+    assert_eq!(encode("Ünicöde", Charset::Base), "{\\\"U}nic{\\\"o}de");
+    assert_eq!(encode("Straße, Łukasiewicz, Gírard", Charset::Base), "Stra{\\ss}e, {\\L}ukasiewicz, G{\\'\\i}rard");
+    assert_eq!(encode("Kočka, ǘ, ç", Charset::Base), "Ko{\\v{c}}ka, {\\'{\\\"u}}, {\\c{c}}");
+    assert_eq!(encode("1–3 α", Charset::Base), "1–3 α");
+}
+
+#[test]
+fn encode_full() {
+    assert_eq!(encode("1–3 “α”\u{00A0}x²", Charset::Full), "1{\\textendash}3 {\\textquotedblleft}$\\alpha${\\textquotedblright}~x$^{2}$");
+    assert_eq!(encode("$α$ {\\\"U} {CALMA}", Charset::Full), "$\\alpha{}$ {\\\"U} {CALMA}");
+}
+
+#[test]
+fn encode_roundtrip() {
+    for charset in [Charset::Base, Charset::Full] {
+        for text in ["Ünicöde", "Erdős–Rényi “graphs” ǘ ı Ø", "The α-β problem in O(n² log n)", "{CALMA} & $x \\sqsubseteq y$"] {
+            assert_eq!(decode_default(&encode(text, charset)), text);
+        }
+    }
+    for text in ["a~b", "1--2", "1---2", "``q''", "?` and !`"] {
+        assert_eq!(decode_default(&encode(text, Charset::Full)), text);
+    }
+    assert_eq!(encode("a~b, 1---2, ``q''", Charset::Full), "a\\textasciitilde{}b, 1-{}-{}-2, `{}`q'{}'");
+    assert_eq!(encode("\\~{n} and $a--b$", Charset::Full), "\\~{n} and $a--b$");
+    assert_eq!(decode_default("1-{}-2, -{}x"), "1--2, -{}x");
+}
+
+#[test]
+fn text_structure() {
+    let input = crate::bibtex::Input::new("<internal test>",
+        "Algorithms for radio link: The {CALMA} project, \\emph{{\\\"O}l} and $\\alpha$");
+    let text = Text::parse(&input.slice());
+    let segments = text.segments();
+    assert_eq!(segments.len(), 6);
+    assert!(matches!(&segments[0], Segment::Plain(span) if span.as_str() == "Algorithms for radio link: The "));
+    assert!(matches!(&segments[1], Segment::Group(group) if group.is_protected() && group.span.as_str() == "{CALMA}"));
+    assert!(matches!(&segments[3], Segment::Command(cmd) if cmd.name.as_str() == "emph" && cmd.args.len() == 1));
+    assert!(matches!(&segments[5], Segment::Math(span) if span.as_str() == "$\\alpha$"));
+    assert_eq!(segments[1].span().trace().col, 32);
+
+    let Segment::Command(emph) = &segments[3] else { unreachable!() };
+    let Segment::Group(special) = &emph.args[0].segments()[0] else { unreachable!() };
+    assert!(!special.is_protected());
+    assert!(!segments[3].is_protected());
+
+    assert_eq!(text.to_string(), input.slice().as_str());
+    assert_eq!(text.plain(), "Algorithms for radio link: The CALMA project, Öl and α");
+}
+
+#[test]
+fn text_commands() {
+    let input = crate::bibtex::Input::new("<internal test>", "\\c c\\ss{} \\cite{a}{b} \\\" o \\\\ x}");
+    let text = Text::parse(&input.slice());
+    let names: Vec<_> = text.segments().iter().filter_map(|segment| match segment {
+        Segment::Command(cmd) => Some((cmd.name.as_str(), cmd.args.len(), cmd.is_protected())),
+        _ => None,
+    }).collect();
+    assert_eq!(names, [("c", 1, false), ("ss", 0, false), ("cite", 2, true), ("\"", 1, false), ("\\", 0, true)]);
+    assert_eq!(text.plain(), "çß ab ö  x}");
+}
+
+#[test]
+fn case_conversion() {
+    let options = CaseOptions::default();
+    let cases = [
+        (
+            "Algorithms for radio link frequency assignment: The {CALMA} project",
+            "Algorithms for radio link frequency assignment: The {CALMA} project",
+            "Algorithms for Radio Link Frequency Assignment: The {CALMA} Project",
+        ),
+        (
+            "Approximating {MAP}s for belief networks is {NP}-hard and other theorems",
+            "Approximating {MAP}s for belief networks is {NP}-hard and other theorems",
+            "Approximating {MAP}s for Belief Networks Is {NP}-Hard and Other Theorems",
+        ),
+        (
+            "Fixed-Parameter Tractability and Completeness {IV}: on Completeness for {$W[P]$} and PSPACE Analogues",
+            "Fixed-parameter tractability and completeness {IV}: On completeness for {$W[P]$} and PSPACE analogues",
+            "Fixed-Parameter Tractability and Completeness {IV}: On Completeness for {$W[P]$} and PSPACE Analogues",
+        ),
+        (
+            "{\\\"O}ber die {\\\"O}konomie in \\emph{Der Welt} of \\LaTeX\\ and {\\O}re \\cite{Key}",
+            "{\\\"O}ber die {\\\"o}konomie in \\emph{der welt} of \\LaTeX\\ and {\\o}re \\cite{Key}",
+            "{\\\"O}ber Die {\\\"O}konomie in \\emph{Der Welt} of \\LaTeX\\ and {\\O}re \\cite{Key}",
+        ),
+    ];
+    for (title, sentence, titled) in cases {
+        let input = crate::bibtex::Input::new("<internal test>", title);
+        let text = Text::parse(&input.slice());
+        assert_eq!(sentence_case(&text, None, &options), sentence);
+        assert_eq!(title_case(&text, Some("english"), &options), titled);
+        assert_eq!(sentence_case(&text, Some("german"), &options), title);
+    }
+}
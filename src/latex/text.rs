@@ -0,0 +1,261 @@
+use std::fmt;
+
+use crate::bibtex::InputSlice;
+use super::{decode, find_unescaped, DecodeOptions};
+use super::symbols::{self, lookup};
+
+
+/// Field value with its LaTeX structure, e.g.
+/// `Algorithms for radio link frequency assignment: The {CALMA} project`.
+#[derive(Debug, Clone, Default)]
+pub struct Text<'de> {
+    segments: Vec<Segment<'de>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Segment<'de> {
+    /// Plain text without any markup.
+    Plain(InputSlice<'de>),
+    /// `{...}`, protected unless it is a BibTeX special character like `{\"O}`.
+    Group(Group<'de>),
+    /// `\"o`, `\ss`, `\emph{...}`.
+    Command(Command<'de>),
+    /// `$...$` or `$$...$$`.
+    Math(InputSlice<'de>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Group<'de> {
+    pub span: InputSlice<'de>,
+    pub content: Text<'de>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Command<'de> {
+    pub span: InputSlice<'de>,
+    pub name: InputSlice<'de>,
+    pub args: Vec<Text<'de>>,
+}
+
+
+// Text
+// ----
+
+impl<'de> Text<'de> {
+
+    pub fn parse(source: &InputSlice<'de>) -> Self {
+        Parser { source, pos: 0 }.parse_text(false)
+    }
+
+    pub fn segments(&self) -> &[Segment<'de>] {
+        &self.segments
+    }
+
+    /// Appends another part of a compound value.
+    pub fn append(&mut self, other: Text<'de>) {
+        self.segments.extend(other.segments);
+    }
+
+    /// Text without markup, for sorting, hashing and non-TeX output.
+    pub fn plain(&self) -> String {
+        self.segments.iter().map(Segment::plain).collect()
+    }
+}
+
+impl fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            f.write_str(segment.span().as_str())?;
+        }
+        Ok(())
+    }
+}
+
+
+// Segments
+// --------
+
+fn plain_options() -> DecodeOptions {
+    DecodeOptions { keep_unknown: false, strip_braces: true, ..Default::default() }
+}
+
+impl<'de> Segment<'de> {
+
+    /// The original source of this segment.
+    pub fn span(&self) -> &InputSlice<'de> {
+        match self {
+            Segment::Plain(span) | Segment::Math(span) => span,
+            Segment::Group(group) => &group.span,
+            Segment::Command(command) => &command.span,
+        }
+    }
+
+    /// Whether case changes must leave this segment as it is.
+    pub fn is_protected(&self) -> bool {
+        match self {
+            Segment::Plain(_) => false,
+            Segment::Group(group) => group.is_protected(),
+            Segment::Command(command) => command.is_protected(),
+            Segment::Math(_) => true,
+        }
+    }
+
+    pub fn plain(&self) -> String {
+        match self {
+            Segment::Plain(span) | Segment::Math(span) => decode(span.as_str(), &plain_options()),
+            Segment::Group(group) => group.content.plain(),
+            Segment::Command(command) if command.is_symbol() => decode(command.span.as_str(), &plain_options()),
+            Segment::Command(command) => command.args.iter().map(Text::plain).collect(),
+        }
+    }
+}
+
+impl Group<'_> {
+
+    /// BibTeX special characters start with a command, all other groups are protected.
+    pub fn is_protected(&self) -> bool {
+        !matches!(self.content.segments.first(), Some(Segment::Command(_)))
+    }
+}
+
+impl Command<'_> {
+
+    /// Accents and special characters like `\"o` or `\ss`.
+    pub fn is_symbol(&self) -> bool {
+        let name = self.name.as_str();
+        lookup(symbols::ACCENTS, name).is_some() || lookup(symbols::SPECIALS, name).is_some()
+    }
+
+    pub fn is_accent(&self) -> bool {
+        lookup(symbols::ACCENTS, self.name.as_str()).is_some()
+    }
+
+    /// Commands other than symbols and text formatting keep their arguments as they are.
+    pub fn is_protected(&self) -> bool {
+        !self.is_symbol() && !symbols::FORMATTING.contains(&self.name.as_str())
+    }
+}
+
+
+// Parser
+// ------
+
+struct Parser<'a, 'de> {
+    source: &'a InputSlice<'de>,
+    pos: usize,
+}
+
+impl<'de> Parser<'_, 'de> {
+
+    fn rest(&self) -> &'de str {
+        &self.source.as_str()[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Parses until the end of the source or, if `nested`, the closing brace.
+    fn parse_text(&mut self, nested: bool) -> Text<'de> {
+        let mut segments = Vec::new();
+        let mut plain = self.pos;
+        while let Some(ch) = self.peek() {
+            if !matches!(ch, '{' | '}' | '\\' | '$') {
+                self.pos += ch.len_utf8();
+                continue;
+            }
+            if plain < self.pos {
+                segments.push(Segment::Plain(self.source.slice(plain..self.pos)));
+            }
+            match ch {
+                '}' if nested => {
+                    self.pos += 1;
+                    return Text { segments };
+                },
+                '}' => {
+                    self.pos += 1;
+                    segments.push(Segment::Plain(self.source.slice(self.pos - 1..self.pos)));
+                },
+                '{' => segments.push(Segment::Group(self.parse_group())),
+                '\\' => segments.push(self.parse_command()),
+                _ => segments.push(self.parse_math()),
+            }
+            plain = self.pos;
+        }
+        if plain < self.pos {
+            segments.push(Segment::Plain(self.source.slice(plain..self.pos)));
+        }
+        Text { segments }
+    }
+
+    fn parse_group(&mut self) -> Group<'de> {
+        let start = self.pos;
+        self.pos += 1;
+        let content = self.parse_text(true);
+        Group { span: self.source.slice(start..self.pos), content }
+    }
+
+    fn parse_command(&mut self) -> Segment<'de> {
+        let start = self.pos;
+        self.pos += 1;
+        let rest = self.rest();
+        let len = match rest.chars().next() {
+            Some(ch) if ch.is_ascii_alphabetic() => {
+                rest.find(|ch: char| !ch.is_ascii_alphabetic()).unwrap_or(rest.len())
+            },
+            Some(ch) => ch.len_utf8(),
+            None => return Segment::Plain(self.source.slice(start..self.pos)),
+        };
+        let name = self.source.slice(self.pos..self.pos + len);
+        self.pos += len;
+        let letters = name.as_str().starts_with(|ch: char| ch.is_ascii_alphabetic());
+        if letters {
+            self.skip_whitespace();
+        }
+
+        let mut args = Vec::new();
+        if lookup(symbols::ACCENTS, name.as_str()).is_some() {
+            self.skip_whitespace();
+            let arg_start = self.pos;
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    args.push(self.parse_text(true));
+                },
+                Some('\\') => args.push(Text { segments: vec![self.parse_command()] }),
+                Some(ch) => {
+                    self.pos += ch.len_utf8();
+                    args.push(Text { segments: vec![Segment::Plain(self.source.slice(arg_start..self.pos))] });
+                },
+                None => (),
+            }
+        } else if letters && lookup(symbols::SPECIALS, name.as_str()).is_none() {
+            while self.peek() == Some('{') {
+                self.pos += 1;
+                args.push(self.parse_text(true));
+            }
+        }
+        Segment::Command(Command { span: self.source.slice(start..self.pos), name, args })
+    }
+
+    fn parse_math(&mut self) -> Segment<'de> {
+        let start = self.pos;
+        let delimiter = if self.rest().starts_with("$$") { "$$" } else { "$" };
+        self.pos += delimiter.len();
+        match find_unescaped(self.rest(), delimiter) {
+            Some(len) => {
+                self.pos += len + delimiter.len();
+                Segment::Math(self.source.slice(start..self.pos))
+            },
+            None => {
+                self.pos = self.source.as_str().len();
+                Segment::Plain(self.source.slice(start..self.pos))
+            },
+        }
+    }
+}
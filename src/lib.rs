@@ -1,7 +1,6 @@
-
-
 #[allow(dead_code)]
-
 pub mod bibtex;
+pub mod bibliography;
 pub mod bcf;
-
+pub mod latex;
+pub mod value;
@@ -0,0 +1,210 @@
+use std::fmt;
+
+
+/// Parsed value of a [`DataType::Date`](crate::bcf::DataType::Date) field in
+/// the ISO8601-2 (EDTF level 1) subset supported by biblatex, e.g.
+/// `2003-06-21`, `1985~/1986?`, `2004/..` or `2005-21` (spring 2005).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Date {
+    Single(DatePoint),
+    /// End is `None` for open ranges like `2004/`.
+    Range(DatePoint, Option<DatePoint>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatePoint {
+    pub year: i32,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    /// `21` to `24` for spring to winter.
+    pub season: Option<u8>,
+    pub time: Option<Time>,
+    /// `?`
+    pub uncertain: bool,
+    /// `~`
+    pub approximate: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// `Z` or an offset like `+01:00`.
+    pub timezone: Option<String>,
+}
+
+
+impl Date {
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        match text.split_once('/') {
+            None => Ok(Date::Single(DatePoint::parse(text)?)),
+            Some((start, end)) => {
+                let start = DatePoint::parse(start.trim())?;
+                let end = match end.trim() {
+                    "" | ".." => None,
+                    end => Some(DatePoint::parse(end)?),
+                };
+                Ok(Date::Range(start, end))
+            },
+        }
+    }
+
+    pub fn start(&self) -> &DatePoint {
+        match self {
+            Date::Single(start) | Date::Range(start, _) => start,
+        }
+    }
+
+    pub fn end(&self) -> Option<&DatePoint> {
+        match self {
+            Date::Single(_) => None,
+            Date::Range(_, end) => end.as_ref(),
+        }
+    }
+}
+
+impl DatePoint {
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid date '{}'", text);
+        let mut point = DatePoint::default();
+
+        // qualifiers apply to the whole date
+        let mut rest = text;
+        loop {
+            match rest.chars().last() {
+                Some('?') => point.uncertain = true,
+                Some('~') => point.approximate = true,
+                Some('%') => {
+                    point.uncertain = true;
+                    point.approximate = true;
+                },
+                _ => break,
+            }
+            rest = &rest[..rest.len() - 1];
+        }
+
+        let (date, time) = match rest.split_once('T') {
+            Some((date, time)) => (date, Some(time)),
+            None => (rest, None),
+        };
+        let (negative, date) = match date.strip_prefix('-') {
+            Some(date) => (true, date),
+            None => (false, date),
+        };
+        let mut parts = date.split('-');
+        let year = parts.next().filter(|year| year.len() >= 4).ok_or_else(invalid)?;
+        point.year = number(year).ok_or_else(invalid)?;
+        if negative {
+            point.year = -point.year;
+        }
+        if let Some(month) = parts.next() {
+            match number(month).filter(|_| month.len() == 2).ok_or_else(invalid)? {
+                month @ 1..=12 => point.month = Some(month as u8),
+                season @ 21..=24 => point.season = Some(season as u8),
+                _ => return Err(invalid()),
+            }
+        }
+        if let Some(day) = parts.next() {
+            let day = number(day).filter(|_| day.len() == 2).ok_or_else(invalid)?;
+            if point.month.is_none() || !(1..=days_in_month(point.year, point.month.unwrap_or(1))).contains(&day) {
+                return Err(invalid());
+            }
+            point.day = Some(day as u8);
+        }
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        if let Some(time) = time {
+            if point.day.is_none() {
+                return Err(invalid());
+            }
+            point.time = Some(Time::parse(time).ok_or_else(invalid)?);
+        }
+        Ok(point)
+    }
+}
+
+impl Time {
+
+    fn parse(text: &str) -> Option<Self> {
+        let split = text.find(['Z', '+', '-']).unwrap_or(text.len());
+        let (clock, zone) = text.split_at(split);
+        let mut parts = clock.split(':');
+        let mut next = |max: i32| parts.next()
+            .filter(|part| part.len() == 2)
+            .and_then(number)
+            .filter(|value| (0..=max).contains(value))
+            .map(|value| value as u8);
+        let hour = next(24)?;
+        let minute = next(59)?;
+        let second = if clock.len() > 5 { next(60)? } else { 0 };
+        if parts.next().is_some() {
+            return None;
+        }
+        let timezone = match zone {
+            "" => None,
+            "Z" => Some(zone.to_owned()),
+            _ if zone.len() == 3 || zone.len() == 6 => Some(zone.to_owned()),
+            _ => return None,
+        };
+        Some(Time { hour, minute, second, timezone })
+    }
+}
+
+
+fn number(text: &str) -> Option<i32> {
+    if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> i32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Date::Single(point) => write!(f, "{}", point),
+            Date::Range(start, Some(end)) => write!(f, "{}/{}", start, end),
+            Date::Range(start, None) => write!(f, "{}/", start),
+        }
+    }
+}
+
+impl fmt::Display for DatePoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.year < 0 {
+            write!(f, "-{:04}", -self.year)?;
+        } else {
+            write!(f, "{:04}", self.year)?;
+        }
+        if let Some(month) = self.month.or(self.season) {
+            write!(f, "-{:02}", month)?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "-{:02}", day)?;
+        }
+        if let Some(time) = &self.time {
+            write!(f, "T{:02}:{:02}:{:02}{}", time.hour, time.minute, time.second, time.timezone.as_deref().unwrap_or(""))?;
+        }
+        match (self.uncertain, self.approximate) {
+            (true, true) => f.write_str("%"),
+            (true, false) => f.write_str("?"),
+            (false, true) => f.write_str("~"),
+            (false, false) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Brace aware splitting of list and separated values.
+
+
+/// Splits at `separator`, ignoring separators inside braces.
+pub(crate) fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (pos, ch) in text.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ if ch == separator && depth == 0 => {
+                parts.push(&text[start..pos]);
+                start = pos + ch.len_utf8();
+            }
+            _ => (),
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Comma separated values (`format="xsv"`), trimmed and without empty items.
+pub(crate) fn split_xsv(text: &str) -> Vec<&str> {
+    split_top_level(text, ',').into_iter()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Items of a BibTeX list, separated by `and` outside of braces, e.g.
+/// `Ernst and Sons and {Ernst and Sons}` has three items.
+pub(crate) fn split_and(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            byte if depth == 0 && byte.is_ascii_whitespace() => {
+                let word = pos + 1;
+                let end = word + 3;
+                if end < bytes.len()
+                    && bytes[word..end].eq_ignore_ascii_case(b"and")
+                    && bytes[end].is_ascii_whitespace()
+                {
+                    items.push(text[start..pos].trim());
+                    start = end + 1;
+                    pos = end;
+                }
+            }
+            _ => (),
+        }
+        pos += 1;
+    }
+    items.push(text[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+
+/// Items of a [`FieldType::List`](crate::bcf::FieldType::List) field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct List<T> {
+    items: Vec<T>,
+    more: bool,
+}
+
+impl<T> List<T> {
+
+    pub fn new(items: Vec<T>, more: bool) -> Self {
+        List { items, more }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The list ended in `and others` (biber's `\true{more...}`).
+    pub fn more(&self) -> bool {
+        self.more
+    }
+
+    /// Number of items shown by biblatex for the `maxitems`/`minitems` (or
+    /// `maxnames`/`minnames`) options: lists longer than `max` are cut to `min`.
+    pub fn visible(&self, max: usize, min: usize) -> usize {
+        if self.items.len() > max {
+            min.clamp(1, self.items.len())
+        } else {
+            self.items.len()
+        }
+    }
+
+    /// Whether the list is printed with a truncation marker like "et al.".
+    pub fn truncated(&self, max: usize, min: usize) -> bool {
+        self.more || self.visible(max, min) < self.items.len()
+    }
+
+    pub(crate) fn map<U, F: FnMut(T) -> U>(self, f: F) -> List<U> {
+        List { items: self.items.into_iter().map(f).collect(), more: self.more }
+    }
+
+    pub(crate) fn try_map<U, E, F: FnMut(T) -> Result<U, E>>(self, f: F) -> Result<List<U>, E> {
+        Ok(List { items: self.items.into_iter().map(f).collect::<Result<_, _>>()?, more: self.more })
+    }
+}
+
+impl<T> From<Vec<T>> for List<T> {
+    fn from(items: Vec<T>) -> Self {
+        List { items, more: false }
+    }
+}
+
+impl<'a> List<&'a str> {
+
+    /// Splits an `and` separated list, respecting braces like `{Ernst and Sons}`.
+    /// A final `and others` sets [`List::more`] instead of adding an item.
+    pub fn split(text: &'a str) -> Self {
+        let mut items = split_and(text);
+        let more = items.last().is_some_and(|item| *item == "others");
+        if more {
+            items.pop();
+        }
+        List { items, more }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::bcf::{DataType, FieldType};
+
+
+mod date;
+mod list;
+mod name;
+mod options;
+mod range;
+mod uri;
+
+pub use date::{Date, DatePoint, Time};
+pub use list::List;
+pub(crate) use list::{split_and, split_xsv};
+pub use name::Name;
+pub use options::{EntryOption, OptionValue};
+pub use range::{Range, RangeList, page_number};
+pub use uri::{encode_uri, normalize_iri};
+
+
+/// Typed value of a field, as determined by its [`FieldType`] and [`DataType`].
+/// Textual content is kept as LaTeX.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Names(List<Name>),
+    Literals(List<String>),
+    Keys(List<String>),
+    Literal(String),
+    Key(String),
+    Integer(i64),
+    Date(Date),
+    Range(RangeList),
+    /// IRI as given by [`normalize_iri`].
+    Uri(String),
+    Verbatim(String),
+    /// Trimmed, without duplicates.
+    Keywords(Vec<String>),
+    Options(Vec<EntryOption>),
+    Code(String),
+}
+
+impl Value {
+
+    /// Parses the LaTeX source of a field. `xsv` marks comma separated fields
+    /// (`format="xsv"` in the datamodel).
+    pub fn parse(text: &str, fieldtype: FieldType, datatype: DataType, xsv: bool) -> Result<Self, String> {
+        Self::parse_with_nameparts(text, fieldtype, datatype, xsv, &name::NAMEPARTS)
+    }
+
+    /// Like [`Value::parse`], with names parsed by [`Name::parse_with_nameparts`].
+    pub fn parse_with_nameparts<S: AsRef<str>>(
+        text: &str, fieldtype: FieldType, datatype: DataType, xsv: bool, nameparts: &[S],
+    ) -> Result<Self, String> {
+        // kept as written, without LaTeX or whitespace handling
+        match datatype {
+            DataType::Verbatim => return Ok(Value::Verbatim(text.to_owned())),
+            DataType::Code => return Ok(Value::Code(text.to_owned())),
+            DataType::Uri => return Ok(Value::Uri(normalize_iri(text))),
+            _ => (),
+        }
+        let text = &normalize_whitespace(text);
+        let owned = |items: Vec<&str>| items.into_iter().map(str::to_owned).collect::<Vec<_>>();
+        let value = match (fieldtype, datatype) {
+            (FieldType::List, DataType::Name) => Value::Names(List::split(text).try_map(|name| Name::parse_with_nameparts(name, nameparts))?),
+            (FieldType::List, DataType::Key | DataType::Entrykey) => Value::Keys(List::split(text).map(str::to_owned)),
+            (FieldType::List, _) => Value::Literals(List::split(text).map(str::to_owned)),
+            (_, DataType::Key | DataType::Entrykey) if xsv => Value::Keys(owned(list::split_xsv(text)).into()),
+            (_, DataType::Literal) if xsv => Value::Literals(owned(list::split_xsv(text)).into()),
+            (_, DataType::Key | DataType::Entrykey) => Value::Key(text.to_owned()),
+            (_, DataType::Integer) => Value::Integer(integer(text).ok_or_else(|| format!("'{}' is not an integer", text))?),
+            // year and month fields are commonly not numeric, e.g. `year = {1992--1993}`
+            (_, DataType::Datepart) => match integer(text).or_else(|| month(text)) {
+                Some(value) => Value::Integer(value),
+                None => Value::Literal(text.to_owned()),
+            },
+            (_, DataType::Date) => Value::Date(Date::parse(text)?),
+            (_, DataType::Range) => Value::Range(RangeList::parse(text)?),
+            (_, DataType::Keyword) => Value::Keywords(options::keywords(text)),
+            (_, DataType::Option) => Value::Options(EntryOption::parse_list(text)?),
+            _ => Value::Literal(text.to_owned()),
+        };
+        Ok(value)
+    }
+}
+
+
+/// Arabic or roman numbers, e.g. `volume = {LXI}`.
+fn integer(text: &str) -> Option<i64> {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        return text.parse().ok();
+    }
+    page_number(text).map(i64::from)
+}
+
+fn month(text: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june",
+        "july", "august", "september", "october", "november", "december",
+    ];
+    let text = text.trim_end_matches('.').to_lowercase();
+    if text.len() < 3 {
+        return None;
+    }
+    MONTHS.iter().position(|month| month.starts_with(&text)).map(|month| month as i64 + 1)
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
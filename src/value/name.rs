@@ -0,0 +1,187 @@
+use crate::latex::{decode, DecodeOptions};
+use super::list::split_top_level;
+
+
+/// A single name of a [`DataType::Name`](crate::bcf::DataType::Name) list.
+/// All parts are kept as LaTeX.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Name {
+    pub family: String,
+    pub given: Option<String>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    /// Further parts of the datamodel's `nameparts`, only given in the
+    /// extended name format, in input order.
+    pub other_parts: Vec<(String, String)>,
+}
+
+/// biblatex's default `nameparts`.
+pub(crate) const NAMEPARTS: [&str; 4] = ["family", "given", "prefix", "suffix"];
+
+impl Name {
+
+    /// Parses the BibTeX forms `First von Last`, `von Last, First` and
+    /// `von Last, Jr, First`, as well as biber's extended name format
+    /// `given=First, family=Last`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        Self::parse_with_nameparts(text, &NAMEPARTS)
+    }
+
+    /// Like [`Name::parse`], with the extended name format restricted to
+    /// `nameparts`, as given by [`Datamodel::nameparts`](crate::bcf::Datamodel::nameparts).
+    pub fn parse_with_nameparts<S: AsRef<str>>(text: &str, nameparts: &[S]) -> Result<Self, String> {
+        let parts: Vec<&str> = split_top_level(text, ',').into_iter().map(str::trim).collect();
+        if parts.iter().all(|part| extended_part(part).is_some()) {
+            return Self::parse_extended(&parts, nameparts);
+        }
+        let name = match parts.as_slice() {
+            [name] => {
+                let tokens = tokens(name);
+                let (last, rest) = tokens.split_last().ok_or("empty name")?;
+                // von part: from the first to the last lowercase word before the family name
+                match rest.iter().position(|token| is_lowercase(token)) {
+                    Some(von_start) => {
+                        let von_end = rest.iter().rposition(|token| is_lowercase(token)).unwrap_or(von_start) + 1;
+                        Name {
+                            family: join(&tokens[von_end..]),
+                            given: non_empty(join(&rest[..von_start])),
+                            prefix: non_empty(join(&rest[von_start..von_end])),
+                            suffix: None,
+                            other_parts: Vec::new(),
+                        }
+                    },
+                    None => Name {
+                        family: last.to_string(),
+                        given: non_empty(join(rest)),
+                        prefix: None,
+                        suffix: None,
+                        other_parts: Vec::new(),
+                    },
+                }
+            },
+            [von_last, rest @ ..] if rest.len() <= 2 => {
+                let tokens = tokens(von_last);
+                let (_, head) = tokens.split_last().ok_or("empty family name")?;
+                let von_end = head.iter().rposition(|token| is_lowercase(token)).map_or(0, |pos| pos + 1);
+                let (suffix, given) = match rest {
+                    [given] => (None, given),
+                    [suffix, given] => (non_empty(suffix.to_string()), given),
+                    _ => unreachable!(),
+                };
+                Name {
+                    family: join(&tokens[von_end..]),
+                    given: non_empty(given.to_string()),
+                    prefix: non_empty(join(&tokens[..von_end])),
+                    suffix,
+                    other_parts: Vec::new(),
+                }
+            },
+            _ => return Err(format!("too many commas in name '{}'", text)),
+        };
+        Ok(name)
+    }
+
+    fn parse_extended<S: AsRef<str>>(parts: &[&str], nameparts: &[S]) -> Result<Self, String> {
+        let mut name = Name::default();
+        for part in parts {
+            let (key, value) = extended_part(part).ok_or("invalid name part")?;
+            let key = key.to_ascii_lowercase();
+            let value = value.to_string();
+            // other keys are per-name options like `useprefix`, not supported here
+            if !nameparts.iter().any(|namepart| namepart.as_ref() == key) {
+                continue;
+            }
+            match key.as_str() {
+                "family" => name.family = value,
+                "given" => name.given = Some(value),
+                "prefix" => name.prefix = Some(value),
+                "suffix" => name.suffix = Some(value),
+                _ => name.other_parts.push((key, value)),
+            }
+        }
+        if name.family.is_empty() {
+            return Err("name without family part".to_owned());
+        }
+        Ok(name)
+    }
+}
+
+
+/// `key=value` part of an extended name.
+fn extended_part(part: &str) -> Option<(&str, &str)> {
+    let (key, value) = part.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+/// Words separated by whitespace or `~` outside of braces.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    for (pos, ch) in text.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && (ch.is_whitespace() || ch == '~') => {
+                if let Some(start) = start.take() {
+                    tokens.push(&text[start..pos]);
+                }
+                continue;
+            },
+            _ => (),
+        }
+        start.get_or_insert(pos);
+    }
+    if let Some(start) = start {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Whether the first letter of a word is lowercase, looking into special
+/// characters like `{\"u}ber` but not into other braces.
+fn is_lowercase(token: &str) -> bool {
+    for (pos, ch) in token.char_indices() {
+        match ch {
+            '{' if token[pos + 1..].starts_with('\\') => {
+                let special = &token[pos..group_end(token, pos)];
+                let options = DecodeOptions { keep_unknown: false, ..Default::default() };
+                return decode(special, &options).chars().find(|ch| ch.is_alphabetic()).is_some_and(char::is_lowercase);
+            },
+            '{' => return false,
+            _ if ch.is_alphabetic() => return ch.is_lowercase(),
+            _ => (),
+        }
+    }
+    false
+}
+
+/// End of the brace group starting at `start`.
+fn group_end(text: &str, start: usize) -> usize {
+    let mut depth = 0usize;
+    for (pos, ch) in text[start..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return start + pos + 1;
+                }
+            },
+            _ => (),
+        }
+    }
+    text.len()
+}
+
+fn join(tokens: &[&str]) -> String {
+    tokens.join(" ")
+}
+
+fn non_empty(text: String) -> Option<String> {
+    if text.is_empty() { None } else { Some(text) }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use super::list::split_xsv;
+
+
+/// A single per-entry option of the `options` field, e.g. `useprefix=true`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryOption {
+    /// Name in lowercase.
+    pub name: String,
+    pub value: OptionValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Boolean(bool),
+    Integer(i64),
+    /// Choices like `uniquename=init` and names like `nametemplates=...`.
+    String(String),
+}
+
+impl EntryOption {
+
+    /// Parses the comma separated `key=value` list of an `options` field. A
+    /// bare key is short for `key=true`. Which options exist depends on the
+    /// style, see [`Bibliography::validate_options`](crate::bibliography::Bibliography::validate_options).
+    pub fn parse_list(text: &str) -> Result<Vec<Self>, String> {
+        split_xsv(text).into_iter().map(EntryOption::parse).collect()
+    }
+
+    /// Values are typed by their form: `true` and `false`, integers, and
+    /// strings for choices like `uniquename=init` and names.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (text.trim(), None),
+        };
+        if name.is_empty() {
+            return Err(format!("entry option without name in '{}'", text));
+        }
+        let value = match value {
+            None => OptionValue::Boolean(true),
+            Some(value) if value.eq_ignore_ascii_case("true") => OptionValue::Boolean(true),
+            Some(value) if value.eq_ignore_ascii_case("false") => OptionValue::Boolean(false),
+            Some(value) => match value.parse() {
+                Ok(number) => OptionValue::Integer(number),
+                Err(_) if !value.is_empty() => OptionValue::String(value.to_owned()),
+                Err(_) => return Err(format!("empty value for entry option '{}'", name)),
+            },
+        };
+        Ok(EntryOption { name: name.to_ascii_lowercase(), value })
+    }
+}
+
+impl fmt::Display for EntryOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            OptionValue::Boolean(value) => write!(f, "{}={}", self.name, value),
+            OptionValue::Integer(value) => write!(f, "{}={}", self.name, value),
+            OptionValue::String(value) => write!(f, "{}={}", self.name, value),
+        }
+    }
+}
+
+
+/// Keywords of a `keywords` field, trimmed and without duplicates.
+pub(crate) fn keywords(text: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for keyword in split_xsv(text) {
+        if !keywords.iter().any(|other| other == keyword) {
+            keywords.push(keyword.to_owned());
+        }
+    }
+    keywords
+}
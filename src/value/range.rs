@@ -0,0 +1,326 @@
+use std::fmt;
+
+use super::list::split_top_level;
+
+
+/// Parsed value of a [`DataType::Range`](crate::bcf::DataType::Range) field,
+/// e.g. `pages = {i-vii, 1--116}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeList {
+    original: String,
+    ranges: Vec<Range>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Range {
+    /// `42`
+    Single(String),
+    /// `42-`
+    Open(String),
+    /// `42-48`
+    Closed(String, String),
+}
+
+
+// Range list
+// ----------
+
+impl RangeList {
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut ranges = Vec::new();
+        for part in split_top_level(text, ',') {
+            let part = part.trim();
+            if part.is_empty() {
+                return Err(format!("empty range in '{}'", text));
+            }
+            ranges.push(Range::parse(part)?);
+        }
+        if ranges.is_empty() {
+            return Err("empty range list".to_owned());
+        }
+        Ok(RangeList { original: text.to_owned(), ranges })
+    }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    pub fn ranges(&self) -> &[Range] {
+        &self.ranges
+    }
+
+    /// Normalised form as written to the `.bbl` by biber, e.g. `968\bibrangedash 980`.
+    pub fn normalised(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for RangeList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\\bibrangessep ")?;
+            }
+            write!(f, "{}", range)?;
+        }
+        Ok(())
+    }
+}
+
+
+// Single range
+// ------------
+
+impl Range {
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let (start, end) = match find_dash(text) {
+            None => return Ok(Range::Single(unbrace(text).to_owned())),
+            Some(dash) => (text[..dash.0].trim(), text[dash.1..].trim()),
+        };
+        if start.is_empty() {
+            return Err(format!("range '{}' has no start", text));
+        }
+        if find_dash(end).is_some() {
+            return Err(format!("range '{}' is malformed", text));
+        }
+        if end.is_empty() {
+            Ok(Range::Open(unbrace(start).to_owned()))
+        } else {
+            Ok(Range::Closed(unbrace(start).to_owned(), unbrace(end).to_owned()))
+        }
+    }
+
+    pub fn start(&self) -> &str {
+        match self {
+            Range::Single(start) | Range::Open(start) | Range::Closed(start, _) => start,
+        }
+    }
+
+    pub fn end(&self) -> Option<&str> {
+        match self {
+            Range::Closed(_, end) => Some(end),
+            _ => None,
+        }
+    }
+
+    /// Number of pages covered by the range (biber's `rangelen`), if both
+    /// ends are arabic or roman numerals. Open ranges have no length.
+    pub fn count(&self) -> Option<u32> {
+        match self {
+            Range::Single(_) => Some(1),
+            Range::Open(_) => None,
+            Range::Closed(start, end) => {
+                let arabic = is_arabic(start) && is_arabic(end);
+                let start = page_number(start)?;
+                let mut end = page_number(end)?;
+                // abbreviated ranges like 1021-35
+                if arabic && end < start && count_digits(end) < count_digits(start) {
+                    let modulus = 10u32.pow(count_digits(end));
+                    end = end.checked_add(start - start % modulus)?;
+                }
+                end.checked_sub(start)?.checked_add(1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Range::Single(start) => f.write_str(start),
+            Range::Open(start) => write!(f, "{}\\bibrangedash", start),
+            Range::Closed(start, end) => write!(f, "{}\\bibrangedash {}", start, end),
+        }
+    }
+}
+
+
+// Page numbers
+// ------------
+
+/// Numeric value of an arabic (`42`) or roman (`xlii`, `XLII`) page number.
+pub fn page_number(page: &str) -> Option<u32> {
+    let page = page.trim();
+    if is_arabic(page) {
+        return page.parse().ok();
+    }
+    roman_number(page)
+}
+
+fn is_arabic(page: &str) -> bool {
+    let page = page.trim();
+    !page.is_empty() && page.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn roman_number(text: &str) -> Option<u32> {
+    if text.is_empty() {
+        return None;
+    }
+    let lower = text.bytes().all(|b| b.is_ascii_lowercase());
+    let upper = text.bytes().all(|b| b.is_ascii_uppercase());
+    if !lower && !upper {
+        return None;
+    }
+    let mut total: u32 = 0;
+    let mut last = 0;
+    for byte in text.bytes().rev() {
+        let value = match byte.to_ascii_lowercase() {
+            b'i' => 1,
+            b'v' => 5,
+            b'x' => 10,
+            b'l' => 50,
+            b'c' => 100,
+            b'd' => 500,
+            b'm' => 1000,
+            _ => return None,
+        };
+        if value < last {
+            total = total.checked_sub(value)?;
+        } else {
+            total = total.checked_add(value)?;
+            last = value;
+        }
+    }
+    // only accept canonical spellings, so that e.g. `mid` stays a word
+    if to_roman(total) == text.to_ascii_lowercase() { Some(total) } else { None }
+}
+
+fn to_roman(mut value: u32) -> String {
+    const DIGITS: [(u32, &str); 13] = [
+        (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"), (100, "c"), (90, "xc"),
+        (50, "l"), (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i"),
+    ];
+    let mut roman = String::new();
+    for (step, digits) in DIGITS {
+        while value >= step {
+            roman.push_str(digits);
+            value -= step;
+        }
+    }
+    roman
+}
+
+fn count_digits(value: u32) -> u32 {
+    value.checked_ilog10().unwrap_or(0) + 1
+}
+
+
+// Utility
+// -------
+
+const DASH_MACROS: [&str; 3] = ["\\bibrangedash", "\\textendash", "\\textemdash"];
+
+fn is_dash(ch: char) -> bool {
+    matches!(ch, '-' | '\u{2010}'..='\u{2015}' | '\u{2212}')
+}
+
+/// Byte span of the first run of dashes outside of braces.
+fn find_dash(text: &str) -> Option<(usize, usize)> {
+    let mut depth = 0usize;
+    let mut found: Option<(usize, usize)> = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((pos, ch)) = chars.next() {
+        let len = match ch {
+            '{' => { depth += 1; 0 },
+            '}' => { depth = depth.saturating_sub(1); 0 },
+            _ if depth > 0 => 0,
+            _ if is_dash(ch) => ch.len_utf8(),
+            '\\' => DASH_MACROS.iter()
+                .find(|name| text[pos..].starts_with(*name)
+                    && !text[pos + name.len()..].starts_with(|c: char| c.is_ascii_alphabetic()))
+                .map(|name| {
+                    let rest = &text[pos + name.len()..];
+                    name.len() + if rest.starts_with("{}") { 2 } else { 0 }
+                })
+                .unwrap_or(0),
+            _ => 0,
+        };
+        match (found, len) {
+            (Some(_), 0) => return found,
+            (None, 0) => (),
+            (_, len) => {
+                let start = found.map_or(pos, |(start, _)| start);
+                found = Some((start, pos + len));
+                skip_to(&mut chars, pos + len);
+            }
+        }
+    }
+    found
+}
+
+fn skip_to(chars: &mut std::iter::Peekable<std::str::CharIndices>, offset: usize) {
+    while chars.next_if(|(pos, _)| *pos < offset).is_some() {}
+}
+
+fn unbrace(text: &str) -> &str {
+    match text.strip_prefix('{').and_then(|inner| inner.strip_suffix('}')) {
+        Some(inner) if !inner.contains(['{', '}']) => inner,
+        _ => text,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_ranges() {
+        for text in ["968 - 980", "968-980", "968--980", "968\u{2013}980", "968 \\textendash{} 980"] {
+            let list = RangeList::parse(text).unwrap();
+            assert_eq!(list.ranges(), &[Range::Closed("968".into(), "980".into())]);
+            assert_eq!(list.original(), text);
+            assert_eq!(list.normalised(), "968\\bibrangedash 980");
+        }
+    }
+
+    #[test]
+    fn multiple_and_open_ranges() {
+        let list = RangeList::parse("i-vii,1-116, 200-, 5").unwrap();
+        assert_eq!(list.ranges(), &[
+            Range::Closed("i".into(), "vii".into()),
+            Range::Closed("1".into(), "116".into()),
+            Range::Open("200".into()),
+            Range::Single("5".into()),
+        ]);
+        assert_eq!(list.normalised(),
+            "i\\bibrangedash vii\\bibrangessep 1\\bibrangedash 116\\bibrangessep 200\\bibrangedash\\bibrangessep 5");
+        assert_eq!(list.ranges()[0].count(), Some(7));
+        assert_eq!(list.ranges()[2].count(), None);
+    }
+
+    #[test]
+    fn non_numeric_pages() {
+        let list = RangeList::parse("{A-12}-{A-15}, S3--S9").unwrap();
+        assert_eq!(list.ranges(), &[
+            Range::Closed("A-12".into(), "A-15".into()),
+            Range::Closed("S3".into(), "S9".into()),
+        ]);
+        assert_eq!(list.ranges()[1].count(), None);
+        assert_eq!(Range::Closed("1021".into(), "35".into()).count(), Some(15));
+        assert_eq!(RangeList::parse("x-v").unwrap().ranges()[0].count(), None);
+        assert_eq!(Range::Closed("xii".into(), "5".into()).count(), None);
+        assert_eq!(RangeList::parse("4294967295-999999999").unwrap().ranges()[0].count(), None);
+        assert_eq!(Range::Closed("0".into(), "4294967295".into()).count(), None);
+    }
+
+    #[test]
+    fn page_numbers() {
+        assert_eq!(page_number("42"), Some(42));
+        assert_eq!(page_number("xlii"), Some(42));
+        assert_eq!(page_number("MCMXC"), Some(1990));
+        assert_eq!(page_number("mid"), None);
+        assert_eq!(page_number("Xi"), None);
+        assert_eq!(page_number("iiiiiiiiiiix"), None);
+        assert_eq!(page_number("ixx"), None);
+    }
+
+    #[test]
+    fn malformed_ranges() {
+        assert!(RangeList::parse("-12").is_err());
+        assert!(RangeList::parse("1-2-3").is_err());
+        assert!(RangeList::parse("1,,2").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt::Write;
+
+use unicode_normalization::UnicodeNormalization;
+
+
+/// IRI form of a [`DataType::Uri`](crate::bcf::DataType::Uri) field, as biber
+/// stores `url`: a BibTeX escaped `\%` becomes `%` and percent-encoded UTF-8
+/// is decoded. Escaped ASCII other than unreserved characters stays encoded,
+/// so `%2F` keeps its meaning.
+pub fn normalize_iri(text: &str) -> String {
+    let text = text.trim().replace("\\%", "%");
+    let bytes = text.as_bytes();
+    let mut iri = String::with_capacity(text.len());
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match escaped(bytes, pos) {
+            Some(byte) if byte.is_ascii() => {
+                if is_unreserved(byte) {
+                    iri.push(byte as char);
+                } else {
+                    let _ = write!(iri, "%{:02X}", byte);
+                }
+                pos += 3;
+            },
+            Some(_) => {
+                let start = pos;
+                let mut run = Vec::new();
+                while let Some(byte) = escaped(bytes, pos).filter(|byte| !byte.is_ascii()) {
+                    run.push(byte);
+                    pos += 3;
+                }
+                match String::from_utf8(run) {
+                    Ok(decoded) => iri.push_str(&decoded),
+                    Err(_) => iri.push_str(&text[start..pos].to_ascii_uppercase()),
+                }
+            },
+            None => {
+                let ch = text[pos..].chars().next().unwrap_or_default();
+                iri.push(ch);
+                pos += ch.len_utf8();
+            },
+        }
+    }
+    iri.nfc().collect()
+}
+
+/// ASCII URI of an IRI, percent-encoding non-ASCII characters and ASCII not
+/// allowed in URIs like spaces. Existing escapes are kept.
+pub fn encode_uri(iri: &str) -> String {
+    let mut uri = String::with_capacity(iri.len());
+    for ch in iri.chars() {
+        if ch.is_ascii() && (is_unreserved(ch as u8) || "%:/?#[]@!$&'()*+,;=".contains(ch)) {
+            uri.push(ch);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in ch.encode_utf8(&mut buffer).bytes() {
+                let _ = write!(uri, "%{:02X}", byte);
+            }
+        }
+    }
+    uri
+}
+
+
+/// Byte encoded as `%XX` at `pos`.
+fn escaped(bytes: &[u8], pos: usize) -> Option<u8> {
+    match bytes.get(pos..pos + 3)? {
+        [b'%', high, low] => {
+            let hex = |digit: u8| (digit as char).to_digit(16);
+            Some((hex(*high)? * 16 + hex(*low)?) as u8)
+        },
+        _ => None,
+    }
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iri_normalization() {
+        assert_eq!(normalize_iri(" http://example.org/M%C3%BCller%7e "), "http://example.org/Müller~");
+        assert_eq!(normalize_iri("http://example.org/a\\%20b%2fc"), "http://example.org/a%20b%2Fc");
+        assert_eq!(normalize_iri("http://example.org/%FF%41"), "http://example.org/%FFA");
+        assert_eq!(normalize_iri("http://example.org/u\u{308}ber"), "http://example.org/\u{fc}ber");
+    }
+
+    #[test]
+    fn uri_encoding() {
+        assert_eq!(encode_uri("http://example.org/Müller?q=a b#x"), "http://example.org/M%C3%BCller?q=a%20b#x");
+        assert_eq!(encode_uri("http://example.org/a%20b"), "http://example.org/a%20b");
+        let iri = "http://example.org/Ünicöde";
+        assert_eq!(normalize_iri(&encode_uri(iri)), iri);
+    }
+}